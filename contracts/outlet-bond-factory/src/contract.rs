@@ -0,0 +1,422 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Reply, SubMsg,
+                    SubMsgExecutionResponse, CosmosMsg, WasmMsg, Order};
+use cw2::set_contract_version;
+
+use crate::error::ContractError;
+use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, ConfigResponse, BondResponse, BondsResponse};
+use crate::state::{Config, CONFIG, BondRecord, BondStatus, BONDS, PENDING_RESERVE};
+
+use cw_controllers::Admin;
+use cw_storage_plus::Bound;
+use cw20::Expiration;
+use outlet_bond::msg::{InstantiateMsg as BondInstantiateMsg};
+use outlet_bond::state::FeeDestination;
+use outlet_treasury::msg::{ExecuteMsg as TreasuryExecuteMsg};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:outlet-bond-factory";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+static FACTORY_ADMIN: &Admin = &Admin::new("factory_admin");
+
+const INSTANTIATE_BOND_REPLY_ID: u64 = 1;
+
+// Pagination for the bonds query
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    mut deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let admin_addr = deps.api.addr_validate(&msg.admin)?;
+    FACTORY_ADMIN.set(deps.branch(), Some(admin_addr))?;
+
+    let config = Config{
+        bond_code_id: msg.bond_code_id,
+        treasury: deps.api.addr_validate(&msg.treasury)?,
+        dao: deps.api.addr_validate(&msg.dao)?,
+        staking: deps.api.addr_validate(&msg.staking)?
+    };
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("owner", info.sender))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::CreateBond {reserve, label, fee_destination, guardian, timelock} =>
+            create_bond(deps, info, reserve, label, fee_destination, guardian, timelock),
+        ExecuteMsg::RetireBond {address} => retire_bond(deps, info, address),
+        ExecuteMsg::SetBondCodeId {code_id} => set_bond_code_id(deps, info, code_id),
+        ExecuteMsg::AcceptTreasuryAdmin {} => accept_treasury_admin(deps, info),
+        ExecuteMsg::ProposeTreasuryAdmin {address, expiry} => propose_treasury_admin(deps, info, address, expiry)
+    }
+}
+
+// New bonds are wired to the factory's treasury, DAO and staking and administered by the factory admin
+pub fn create_bond(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    reserve: String,
+    label: String,
+    fee_destination: Option<FeeDestination>,
+    guardian: Option<String>,
+    timelock: Option<u64>
+) -> Result<Response, ContractError> {
+    only_admin(deps.branch(), info.clone())?;
+    let config = CONFIG.load(deps.storage)?;
+
+    let bond_msg = BondInstantiateMsg{
+        admin: info.sender.to_string(),
+        treasury: config.treasury.to_string(),
+        dao: config.dao.to_string(),
+        staking: config.staking.to_string(),
+        fee_destination,
+        guardian,
+        timelock,
+        quote_denom: Some(reserve.clone()),
+        wrapped_token: None,
+        total_debt: 0,
+        last_decay: 0
+    };
+    PENDING_RESERVE.save(deps.storage, &reserve)?;
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(CosmosMsg::Wasm(WasmMsg::Instantiate{
+            admin: Some(info.sender.to_string()),
+            code_id: config.bond_code_id,
+            msg: to_binary(&bond_msg)?,
+            funds: vec![],
+            label
+        }), INSTANTIATE_BOND_REPLY_ID))
+        .add_attribute("method", "create_bond")
+        .add_attribute("reserve", reserve))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    if msg.id != INSTANTIATE_BOND_REPLY_ID {
+        return Err(ContractError::UnknownReply{ id: msg.id })
+    }
+    let response = match msg.result.into_result() {
+        Ok(response) => response,
+        Err(_) => return Err(ContractError::MissingContractAddress{})
+    };
+    let bond = match instantiated_address(&response) {
+        Some(address) => deps.api.addr_validate(&address)?,
+        None => return Err(ContractError::MissingContractAddress{})
+    };
+
+    let reserve = PENDING_RESERVE.load(deps.storage)?;
+    PENDING_RESERVE.remove(deps.storage);
+    BONDS.save(deps.storage, &bond, &BondRecord{ reserve: reserve.clone(), status: BondStatus::Active })?;
+
+    // The factory has to hold the treasury admin for this to go through
+    let config = CONFIG.load(deps.storage)?;
+    Ok(Response::new()
+        .add_message(toggle_depositor(&config, bond.to_string())?)
+        .add_attribute("method", "register_bond")
+        .add_attribute("bond", bond)
+        .add_attribute("reserve", reserve))
+}
+
+// Older chains emit `instantiate_contract`/`contract_address`, wasmd 0.16+ `instantiate`/`_contract_address`
+fn instantiated_address(response: &SubMsgExecutionResponse) -> Option<String> {
+    response.events
+        .iter()
+        .filter(|event| event.ty == "instantiate" || event.ty == "instantiate_contract")
+        .flat_map(|event| event.attributes.iter())
+        .find(|attr| attr.key == "_contract_address" || attr.key == "contract_address")
+        .map(|attr| attr.value.clone())
+}
+
+// The treasury toggles depositors, so retiring sends the same message as registering
+pub fn retire_bond(mut deps: DepsMut, info: MessageInfo, address: String) -> Result<Response, ContractError> {
+    only_admin(deps.branch(), info)?;
+    let bond = deps.api.addr_validate(&address)?;
+    let mut record = BONDS.load(deps.storage, &bond)?;
+    if record.status == BondStatus::Retired {
+        return Err(ContractError::AlreadyRetired{})
+    }
+    record.status = BondStatus::Retired;
+    BONDS.save(deps.storage, &bond, &record)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    Ok(Response::new()
+        .add_message(toggle_depositor(&config, address.clone())?)
+        .add_attribute("method", "retire_bond")
+        .add_attribute("bond", address))
+}
+
+fn toggle_depositor(config: &Config, address: String) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute{
+        funds: vec![],
+        contract_addr: config.treasury.to_string(),
+        msg: to_binary(&TreasuryExecuteMsg::AddRemoveDepositor{ address })?
+    }))
+}
+
+pub fn set_bond_code_id(mut deps: DepsMut, info: MessageInfo, code_id: u64) -> Result<Response, ContractError> {
+    only_admin(deps.branch(), info)?;
+    CONFIG.update(deps.storage, |mut config| -> StdResult<_>{
+        config.bond_code_id = code_id;
+        Ok(config)
+    })?;
+    Ok(Response::new()
+        .add_attribute("method", "set_bond_code_id")
+        .add_attribute("code_id", code_id.to_string()))
+}
+
+// Completes a treasury admin handover proposed to the factory
+pub fn accept_treasury_admin(mut deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    only_admin(deps.branch(), info)?;
+    let config = CONFIG.load(deps.storage)?;
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute{
+            funds: vec![],
+            contract_addr: config.treasury.to_string(),
+            msg: to_binary(&TreasuryExecuteMsg::AcceptAdmin{})?
+        }))
+        .add_attribute("method", "accept_treasury_admin"))
+}
+
+// Hands the treasury admin back out of the factory
+pub fn propose_treasury_admin(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    expiry: Option<Expiration>
+) -> Result<Response, ContractError> {
+    only_admin(deps.branch(), info)?;
+    let config = CONFIG.load(deps.storage)?;
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute{
+            funds: vec![],
+            contract_addr: config.treasury.to_string(),
+            msg: to_binary(&TreasuryExecuteMsg::ProposeNewAdmin{ address: address.clone(), expiry })?
+        }))
+        .add_attribute("method", "propose_treasury_admin")
+        .add_attribute("pending_admin", address))
+}
+
+fn only_admin(deps: DepsMut, info: MessageInfo) -> Result< (),ContractError>{
+    FACTORY_ADMIN.assert_admin(deps.as_ref(), &info.sender).map_err(|_not_admin| ContractError::Unauthorized{})
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config{} => to_binary(&query_config(deps)?),
+        QueryMsg::Bond{address} => to_binary(&query_bond(deps, address)?),
+        QueryMsg::Bonds{start_after, limit} => to_binary(&query_bonds(deps, start_after, limit)?)
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let admin = FACTORY_ADMIN.get(deps)?;
+    Ok(ConfigResponse{
+        admin: admin.map(String::from),
+        bond_code_id: config.bond_code_id,
+        treasury: config.treasury.to_string(),
+        dao: config.dao.to_string(),
+        staking: config.staking.to_string()
+    })
+}
+
+fn query_bond(deps: Deps, address: String) -> StdResult<BondResponse> {
+    let bond = deps.api.addr_validate(&address)?;
+    let record = BONDS.load(deps.storage, &bond)?;
+    Ok(BondResponse{
+        address,
+        reserve: record.reserve,
+        status: record.status
+    })
+}
+
+fn query_bonds(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<BondsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = match start_after {
+        Some(address) => Some(Bound::exclusive(deps.api.addr_validate(&address)?.as_bytes())),
+        None => None
+    };
+    let bonds = BONDS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (key, record) = item?;
+            Ok(BondResponse{
+                address: String::from_utf8_lossy(&key).to_string(),
+                reserve: record.reserve,
+                status: record.status
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(BondsResponse{ bonds })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{from_binary, ContractResult, Event};
+
+    const ADMIN: &str = "terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8";
+    const TREASURY: &str = "terra1e8ryd9ezefuucd4mje33zdms9m2s90m57878v9";
+    const BOND: &str = "terra1wjz4hmnrdm4ycn8mnrcjq84j4z9cxe5mmysh53";
+
+    fn init_msg() -> InstantiateMsg {
+        InstantiateMsg {
+            admin: ADMIN.to_string(),
+            bond_code_id: 7,
+            treasury: TREASURY.to_string(),
+            dao: ADMIN.to_string(),
+            staking: ADMIN.to_string()
+        }
+    }
+
+    fn instantiate_reply(event: &str, key: &str) -> Reply {
+        Reply {
+            id: INSTANTIATE_BOND_REPLY_ID,
+            result: ContractResult::Ok(SubMsgExecutionResponse{
+                events: vec![Event::new(event).add_attribute(key, BOND)],
+                data: None
+            })
+        }
+    }
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = mock_dependencies(&[]);
+        let res = instantiate(deps.as_mut(), mock_env(), mock_info(ADMIN, &[]), init_msg()).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Config{}).unwrap();
+        let config: ConfigResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(ADMIN.to_string()), config.admin);
+        assert_eq!(7, config.bond_code_id);
+        assert_eq!(TREASURY, config.treasury);
+    }
+
+    #[test]
+    fn create_bond() {
+        let mut deps = mock_dependencies(&[]);
+        let admin = mock_info(ADMIN, &[]);
+        instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg()).unwrap();
+
+        let create_msg = ExecuteMsg::CreateBond{
+            reserve: "uusd".to_string(),
+            label: "UST bond".to_string(),
+            fee_destination: None,
+            guardian: None,
+            timelock: Some(100)
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(BOND, &[]), create_msg.clone());
+        match res {
+            Err(ContractError::Unauthorized{}) => {},
+            _ => panic!("Must return unauthorized error")
+        }
+
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), create_msg).unwrap();
+        assert_eq!(res.messages[0], SubMsg::reply_on_success(CosmosMsg::Wasm(WasmMsg::Instantiate{
+            admin: Some(ADMIN.to_string()),
+            code_id: 7,
+            msg: to_binary(&BondInstantiateMsg{
+                admin: ADMIN.to_string(),
+                treasury: TREASURY.to_string(),
+                dao: ADMIN.to_string(),
+                staking: ADMIN.to_string(),
+                fee_destination: None,
+                guardian: None,
+                timelock: Some(100),
+                quote_denom: Some("uusd".to_string()),
+                wrapped_token: None,
+                total_debt: 0,
+                last_decay: 0
+            }).unwrap(),
+            funds: vec![],
+            label: "UST bond".to_string()
+        }), INSTANTIATE_BOND_REPLY_ID));
+
+        // The new bond is registered as a treasury depositor
+        let res = reply(deps.as_mut(), mock_env(), instantiate_reply("instantiate", "_contract_address")).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+            funds: vec![],
+            contract_addr: TREASURY.to_string(),
+            msg: to_binary(&TreasuryExecuteMsg::AddRemoveDepositor{ address: BOND.to_string() }).unwrap()
+        }));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Bonds{ start_after: None, limit: None }).unwrap();
+        let bonds: BondsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![BondResponse{
+            address: BOND.to_string(),
+            reserve: "uusd".to_string(),
+            status: BondStatus::Active
+        }], bonds.bonds);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Bonds{ start_after: Some(BOND.to_string()), limit: None }).unwrap();
+        let bonds: BondsResponse = from_binary(&res).unwrap();
+        assert!(bonds.bonds.is_empty());
+    }
+
+    #[test]
+    fn legacy_instantiate_event() {
+        let mut deps = mock_dependencies(&[]);
+        instantiate(deps.as_mut(), mock_env(), mock_info(ADMIN, &[]), init_msg()).unwrap();
+        PENDING_RESERVE.save(deps.as_mut().storage, &"uluna".to_string()).unwrap();
+
+        reply(deps.as_mut(), mock_env(), instantiate_reply("instantiate_contract", "contract_address")).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Bond{ address: BOND.to_string() }).unwrap();
+        let bond: BondResponse = from_binary(&res).unwrap();
+        assert_eq!("uluna", bond.reserve);
+
+        let res = reply(deps.as_mut(), mock_env(), instantiate_reply("wasm", "contract_address"));
+        match res {
+            Err(ContractError::MissingContractAddress{}) => {},
+            _ => panic!("Must return missing contract address error")
+        }
+    }
+
+    #[test]
+    fn retire_bond() {
+        let mut deps = mock_dependencies(&[]);
+        let admin = mock_info(ADMIN, &[]);
+        instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg()).unwrap();
+        PENDING_RESERVE.save(deps.as_mut().storage, &"uusd".to_string()).unwrap();
+        reply(deps.as_mut(), mock_env(), instantiate_reply("instantiate", "_contract_address")).unwrap();
+
+        let retire_msg = ExecuteMsg::RetireBond{ address: BOND.to_string() };
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), retire_msg.clone()).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+            funds: vec![],
+            contract_addr: TREASURY.to_string(),
+            msg: to_binary(&TreasuryExecuteMsg::AddRemoveDepositor{ address: BOND.to_string() }).unwrap()
+        }));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Bond{ address: BOND.to_string() }).unwrap();
+        let bond: BondResponse = from_binary(&res).unwrap();
+        assert_eq!(BondStatus::Retired, bond.status);
+
+        let res = execute(deps.as_mut(), mock_env(), admin, retire_msg);
+        match res {
+            Err(ContractError::AlreadyRetired{}) => {},
+            _ => panic!("Must return already retired error")
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cw20::Expiration;
+
+use crate::state::BondStatus;
+use outlet_bond::state::FeeDestination;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub admin: String,
+    pub bond_code_id: u64,
+    pub treasury: String,
+    pub dao: String,
+    pub staking: String
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    CreateBond{
+        reserve: String,
+        label: String,
+        fee_destination: Option<FeeDestination>,
+        guardian: Option<String>,
+        timelock: Option<u64>
+    },
+    RetireBond{
+        address: String
+    },
+    SetBondCodeId{
+        code_id: u64
+    },
+    AcceptTreasuryAdmin{},
+    ProposeTreasuryAdmin{
+        address: String,
+        expiry: Option<Expiration>
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config{},
+    Bond{address: String},
+    Bonds{start_after: Option<String>, limit: Option<u32>}
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub admin: Option<String>,
+    pub bond_code_id: u64,
+    pub treasury: String,
+    pub dao: String,
+    pub staking: String
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BondResponse {
+    pub address: String,
+    pub reserve: String,
+    pub status: BondStatus
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BondsResponse {
+    pub bonds: Vec<BondResponse>
+}
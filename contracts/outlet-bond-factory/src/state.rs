@@ -0,0 +1,33 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::Addr;
+use cw_storage_plus::{Item, Map};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub bond_code_id: u64,
+    pub treasury: Addr,
+    pub dao: Addr,
+    pub staking: Addr
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BondStatus {
+    Active,
+    Retired
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BondRecord {
+    pub reserve: String,
+    pub status: BondStatus
+}
+
+pub const BONDS: Map<&Addr, BondRecord> = Map::new("bonds");
+
+// Reserve of the bond being instantiated, read back when the instantiate reply arrives
+pub const PENDING_RESERVE: Item<String> = Item::new("pending_reserve");
@@ -3,7 +3,12 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use outlet_bond::msg::{CountResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+use outlet_bond::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, BondInfoResponse, BondPriceInUsdResponse,
+                        MaxPayoutResponse, PendingPayoutResponse, ConfigResponse, TermsResponse, StateResponse, CurrentDebtResponse, DebtRatioResponse, StandardizedDebtRatioResponse,
+                        BondPriceResponse, PercentVestedForResponse, FeesCollectedResponse,
+                        PauseStatusResponse, AdminResponse, PendingChangesResponse,
+                        MarketPriceResponse, CurrentCapacityResponse, MarketInfoResponse, MarketsResponse, EarlyExitResponse,
+                        MaturedBondsResponse, AllBondsResponse};
 use outlet_bond::state::State;
 
 fn main() {
@@ -15,6 +20,29 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(State), &out_dir);
-    export_schema(&schema_for!(CountResponse), &out_dir);
+    export_schema(&schema_for!(BondInfoResponse), &out_dir);
+    export_schema(&schema_for!(BondPriceInUsdResponse), &out_dir);
+    export_schema(&schema_for!(MaxPayoutResponse), &out_dir);
+    export_schema(&schema_for!(PendingPayoutResponse), &out_dir);
+    export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(TermsResponse), &out_dir);
+    export_schema(&schema_for!(StateResponse), &out_dir);
+    export_schema(&schema_for!(CurrentDebtResponse), &out_dir);
+    export_schema(&schema_for!(DebtRatioResponse), &out_dir);
+    export_schema(&schema_for!(StandardizedDebtRatioResponse), &out_dir);
+    export_schema(&schema_for!(BondPriceResponse), &out_dir);
+    export_schema(&schema_for!(PercentVestedForResponse), &out_dir);
+    export_schema(&schema_for!(FeesCollectedResponse), &out_dir);
+    export_schema(&schema_for!(PauseStatusResponse), &out_dir);
+    export_schema(&schema_for!(AdminResponse), &out_dir);
+    export_schema(&schema_for!(PendingChangesResponse), &out_dir);
+    export_schema(&schema_for!(MarketPriceResponse), &out_dir);
+    export_schema(&schema_for!(CurrentCapacityResponse), &out_dir);
+    export_schema(&schema_for!(MarketInfoResponse), &out_dir);
+    export_schema(&schema_for!(MarketsResponse), &out_dir);
+    export_schema(&schema_for!(EarlyExitResponse), &out_dir);
+    export_schema(&schema_for!(MaturedBondsResponse), &out_dir);
+    export_schema(&schema_for!(AllBondsResponse), &out_dir);
 }
@@ -1,23 +1,61 @@
+use std::convert::TryFrom;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
-                    QueryRequest, WasmQuery, Uint128, Coin, Addr, SubMsg, CosmosMsg, WasmMsg};
+use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, StdError,
+                    QueryRequest, WasmQuery, Uint128, Coin, Addr, SubMsg, CosmosMsg, WasmMsg, Event, Order, Storage};
 use cw2::set_contract_version;
 
 use crate::error::ContractError;
-use crate::msg::{ ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{State, STATE, Terms, TERMS, Bond, BOND, Adjust, ADJUST};
+use crate::pricing::{PricingModel, PriceInputs, AuctionState};
+use crate::msg::{ ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, BondInfoResponse, BondPriceInUsdResponse,
+                    MaxPayoutResponse, PendingPayoutResponse, ConfigResponse, TermsResponse, StateResponse,
+                    CurrentDebtResponse, DebtRatioResponse, StandardizedDebtRatioResponse, BondPriceResponse,
+                    PercentVestedForResponse, FeesCollectedResponse, PauseStatusResponse,
+                    AdminResponse, PendingChangeResponse, PendingChangesResponse, MarketPriceResponse,
+                    CurrentCapacityResponse, MarketInfoResponse, MarketResponse, MarketsResponse, EarlyExitResponse,
+                    MaturedBondResponse, MaturedBondsResponse, MaturedCursor, BondResponse, AllBondsResponse};
+use crate::state::{State, STATE, Terms, Bond, bonds, BOND_COUNT, Adjust, FeeDestination, FeesCollected,
+                    FEES_COLLECTED, PauseStatus, PAUSE_STATUS, PendingAdmin, PENDING_ADMIN, ParamChange, PendingChange,
+                    PENDING_CHANGES, CHANGE_COUNT, BondParameter, Market, MARKETS, MARKET_COUNT, DEFAULT_QUOTE, VestingUnit, VestingCurve,
+                    EarlyExitTerms, EARLY_EXIT, REDEEM_BATCH_LIMIT, MATURES_AT_HEIGHT, MATURES_AT_TIME,
+                    LEGACY_DEBT, LEGACY_TERMS, LEGACY_ADJUST, LEGACY_BOND};
 
 use cw_controllers::Admin;
-use cw20::{TokenInfoResponse, Cw20QueryMsg };
+use cw_storage_plus::{Bound, PrimaryKey, U8Key, U64Key};
+use cw20::{TokenInfoResponse, Cw20QueryMsg, Expiration};
 use terra_cosmwasm::TerraQuerier;
 use cosmwasm_bignumber::{Uint256, Decimal256};
 use outlet_treasury::msg::{ExecuteMsg as TreasuryExecuteMsg};
+use outlet_staking::msg::{ExecuteMsg as StakingExecuteMsg, QueryMsg as StakingQueryMsg, SharesForResponse,
+                        WarmupExemptResponse};
+use outlet_wrapped_staked::msg::ReceiveMsg as WrapMsg;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:outlet-bond";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 static BOND_ADMIN: &Admin = &Admin::new("bond_admin");
+static BOND_GUARDIAN: &Admin = &Admin::new("bond_guardian");
+
+// Bounds enforced by SetBondTerms
+const MIN_VESTING_TERM: u64 = 10000;
+// 36 hours, about what the block minimum comes to
+const MIN_VESTING_SECONDS: u64 = 129600;
+// max_payout is a percentage of the PHS supply
+const MAX_PAYOUT_LIMIT: u64 = 1;
+// fee is in thousandths of a percent of the payout
+const MAX_FEE: u64 = 10000;
+const MAX_EARLY_EXIT_PENALTY: u64 = 10000;
+
+// Init configures this market, which buys UST like the single market contract did
+const DEFAULT_MARKET: u64 = 0;
+const DEFAULT_QUOTE_DENOM: &str = "uusd";
+
+// Pagination for the markets query
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+// RedeemMany batch size until the admin sets one
+const DEFAULT_REDEEM_BATCH_LIMIT: u32 = 30;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -29,15 +67,28 @@ pub fn instantiate(
     let admin_addr = deps.api.addr_validate(&msg.admin)?;
     BOND_ADMIN.set(deps.branch(), Some(admin_addr))?;
 
+    let guardian_addr = match msg.guardian {
+        Some(guardian) => Some(deps.api.addr_validate(&guardian)?),
+        None => None
+    };
+    BOND_GUARDIAN.set(deps.branch(), guardian_addr)?;
+    PAUSE_STATUS.save(deps.storage, &PauseStatus::default())?;
+
     let state = State {
         treasury: deps.api.addr_validate(&msg.treasury)?,
         dao: deps.api.addr_validate(&msg.dao)?,
         staking: deps.api.addr_validate(&msg.staking)?,
-        total_debt: 0,
-        last_decay: 0
+        fee_destination: msg.fee_destination.unwrap_or(FeeDestination::Dao),
+        timelock: msg.timelock.unwrap_or(0),
+        wrapped_token: match msg.wrapped_token {
+            Some(address) => Some(deps.api.addr_validate(&address)?),
+            None => None
+        }
     };
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     STATE.save(deps.storage, &state)?;
+    DEFAULT_QUOTE.save(deps.storage, &msg.quote_denom.unwrap_or_else(|| DEFAULT_QUOTE_DENOM.to_string()))?;
+    FEES_COLLECTED.save(deps.storage, &FeesCollected::default())?;
 
     Ok(Response::new()
         .add_attribute("method", "instantiate")
@@ -53,142 +104,546 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::Init { control_variable, vesting_term, minimum_price, max_payout,
-                            fee, max_debt, initial_debt} =>
-                            initialize_bond_terms(deps, env, info, control_variable,
-                                                    vesting_term, minimum_price, max_payout, fee, max_debt, initial_debt),
+                            fee, max_debt, initial_debt, fixed_expiry, vesting_unit, cliff, curve, wrapped_payout, pricing} =>
+                            initialize_bond_terms(deps, env, info, Terms{
+                                control_variable,
+                                vesting_term,
+                                minimum_price,
+                                max_payout,
+                                fee,
+                                max_debt,
+                                fixed_expiry,
+                                vesting_unit: vesting_unit.unwrap_or_default(),
+                                cliff: cliff.unwrap_or_default(),
+                                curve: curve.unwrap_or_default(),
+                                wrapped_payout: wrapped_payout.unwrap_or_default(),
+                                pricing: pricing.unwrap_or_default()
+                            }, initial_debt),
+        ExecuteMsg::CreateMarket { quote_denom, control_variable, vesting_term, minimum_price, max_payout,
+                            fee, max_debt, initial_debt, fixed_expiry, vesting_unit, cliff, curve, wrapped_payout, pricing} =>
+                            create_market(deps, env, info, quote_denom, Terms{
+                                control_variable,
+                                vesting_term,
+                                minimum_price,
+                                max_payout,
+                                fee,
+                                max_debt,
+                                fixed_expiry,
+                                vesting_unit: vesting_unit.unwrap_or_default(),
+                                cliff: cliff.unwrap_or_default(),
+                                curve: curve.unwrap_or_default(),
+                                wrapped_payout: wrapped_payout.unwrap_or_default(),
+                                pricing: pricing.unwrap_or_default()
+                            }, initial_debt),
+        ExecuteMsg::CloseMarket {market_id} => close_market(deps, info, market_id),
         ExecuteMsg::SetStaking {staking} => set_staking(deps, info, staking),
-        ExecuteMsg::Deposit{max_price} => deposit(deps, env, info, max_price),
-        ExecuteMsg::SetAdjustment{ addition, increment, target, buffer } => set_adjustment( deps, info, env,addition, increment, target, buffer  ),
-        ExecuteMsg::Redeem {stake} => redeem( deps, env, info, stake )
+        ExecuteMsg::SetWrappedToken {address} => set_wrapped_token(deps, info, address),
+        ExecuteMsg::SetBondTerms {market_id, parameter, value} => set_bond_terms(deps, info, market_id, parameter, value),
+        ExecuteMsg::SetFeeDestination {destination} => set_fee_destination(deps, info, destination),
+        ExecuteMsg::SetEarlyExit {penalty, destination} => set_early_exit(deps, info, penalty, destination),
+        ExecuteMsg::EarlyExit {bond_id} => early_exit(deps, env, info, bond_id),
+        ExecuteMsg::Deposit{market_id, max_price} => deposit(deps, env, info, market_id, max_price),
+        ExecuteMsg::SetAdjustment{ market_id, addition, increment, target, buffer } =>
+            set_adjustment(deps, info, market_id, new_adjust(&env, addition, increment, target, buffer)),
+        ExecuteMsg::Redeem {market_id, stake, amount} => redeem( deps, env, info, market_id, stake, amount ),
+        ExecuteMsg::RedeemMany {addresses, stake} => redeem_many(deps, env, addresses, stake),
+        ExecuteMsg::SetRedeemBatchLimit {limit} => set_redeem_batch_limit(deps, info, limit),
+        ExecuteMsg::SetGuardian {address} => set_guardian(deps, info, address),
+        ExecuteMsg::Pause {deposits, redemptions} => pause(deps, info, deposits, redemptions),
+        ExecuteMsg::Unpause {deposits, redemptions} => unpause(deps, info, deposits, redemptions),
+        ExecuteMsg::AllowVestedRedemptions {allowed} => allow_vested_redemptions(deps, info, allowed),
+        ExecuteMsg::ProposeNewAdmin {address, expiry} => propose_new_admin(deps, info, address, expiry),
+        ExecuteMsg::AcceptAdmin {} => accept_admin(deps, env, info),
+        ExecuteMsg::RenounceAdmin {} => renounce_admin(deps, info),
+        ExecuteMsg::ProposeChange {change} => propose_change(deps, env, info, change),
+        ExecuteMsg::ExecuteChange {id} => execute_change(deps, env, id),
+        ExecuteMsg::CancelChange {id} => cancel_change(deps, info, id)
         // ExecuteMsg::Increment {} => try_increment(deps),
         // ExecuteMsg::Reset { count } => try_reset(deps, info, count),
     }
 }
 
-pub fn redeem(deps: DepsMut, env: Env, info: MessageInfo, stake: bool) -> Result<Response, ContractError> {
+pub fn redeem(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    market_id: u64,
+    stake: bool,
+    amount: Option<Uint128>
+) -> Result<Response, ContractError> {
+    load_market(deps.storage, market_id)?;
+    let state = STATE.load(deps.storage)?;
+    let (key, _) = bonds().idx.owner.item(deps.storage, (info.sender, U64Key::new(market_id)))?
+        .ok_or(ContractError::NoBond{})?;
+    let bond_id = bond_id(&key);
+    let amount = amount.map(|amount| u64::try_from(amount.u128()).unwrap_or(u64::MAX));
+    let (bond, payout) = redeem_bond(deps.storage, &env, bond_id, amount)?;
+    pay_out(&state, &bond, stake, payout)
+}
+
+// Positions with nothing vested, or held back by a pause, are left for a later batch
+pub fn redeem_many(deps: DepsMut, env: Env, addresses: Vec<String>, stake: bool) -> Result<Response, ContractError> {
+    let limit = REDEEM_BATCH_LIMIT.may_load(deps.storage)?.unwrap_or(DEFAULT_REDEEM_BATCH_LIMIT);
+    if addresses.len() > limit as usize {
+        return Err(ContractError::BatchTooLarge{ limit })
+    }
     let state = STATE.load(deps.storage)?;
-    let treasury_address = String::from(state.treasury);
-    let recipient = info.clone().sender;
-    let bond = bond_info(deps.as_ref(), recipient.clone().to_string())?;
-    let percent_vested = percent_vested_for(deps.as_ref(), env.clone(), recipient.clone().to_string());
-
-    if percent_vested >= 10000u64 {
-        BOND.remove(deps.storage, &recipient);
-        return stake_or_send(
-            info.clone(), 
-            state.staking.to_string(), 
-            treasury_address, 
-            recipient,
-            stake,
-            bond.payout
-        )
+    let mut response = Response::new();
+    let mut redeemed = 0u64;
+    for address in addresses {
+        let owner = deps.api.addr_validate(&address)?;
+        let bond_ids = bonds().idx.owner
+            .prefix(owner)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(key, _)| bond_id(&key)))
+            .collect::<StdResult<Vec<_>>>()?;
+        for bond_id in bond_ids {
+            let (bond, payout) = match redeem_bond(deps.storage, &env, bond_id, None) {
+                Ok(redeemed) => redeemed,
+                Err(ContractError::NothingToRedeem{}) | Err(ContractError::Paused{}) => continue,
+                Err(err) => return Err(err)
+            };
+            response = response.add_submessages(pay_out(&state, &bond, stake, payout)?.messages);
+            redeemed += 1;
+        }
+    }
+    Ok(response
+        .add_attribute("method", "redeem_many")
+        .add_attribute("redeemed", redeemed.to_string()))
+}
+
+// Takes `amount`, or everything that has vested, off a position and returns what is left of it with the payout
+fn redeem_bond(storage: &mut dyn Storage, env: &Env, bond_id: u64, amount: Option<u64>) -> Result<(Bond, u64), ContractError> {
+    let bond = bonds().may_load(storage, U64Key::new(bond_id))?.ok_or(ContractError::NoBond{})?;
+    let percent_vested = percent_vested(&bond, env);
+
+    let pause_status = PAUSE_STATUS.may_load(storage)?.unwrap_or_default();
+    if pause_status.redemptions && !(pause_status.allow_vested_redemptions && percent_vested >= 10000u64) {
+        return Err(ContractError::Paused{})
+    }
+
+    let scheduled = if percent_vested >= 10000u64 {
+        bond.payout
+    } else {
+        bond.payout * percent_vested / 10000
+    };
+    // Whatever vested but is not claimed now stays claimable later
+    let available = bond.claimable + scheduled;
+    let payout = match amount {
+        Some(amount) => {
+            if amount > available {
+                return Err(ContractError::ExceedsVested{ vested: available })
+            }
+            amount
+        },
+        None => available
+    };
+    if payout == 0 {
+        return Err(ContractError::NothingToRedeem{})
+    }
+
+    if percent_vested >= 10000u64 && payout == available {
+        bonds().replace(storage, U64Key::new(bond_id), None, Some(&bond))?;
+        Ok((bond, payout))
     }
     else{
-        let payout = bond.payout * percent_vested / 10000;
+        // Only the vested part of the period is consumed so step curves keep their cadence
+        let (vested, total) = vesting_progress(&bond, env);
+        let (last_block, last_time) = match (bond.expiry, &bond.vesting_unit) {
+            (None, VestingUnit::Blocks) => (bond.last_block + vested, env.block.time.seconds()),
+            _ => (env.block.height, bond.last_time + vested)
+        };
         let bond_info_to_save = Bond{
-            payout: bond.payout - payout,
-            vesting: bond.vesting - (env.clone().block.height - bond.last_block),
-            last_block: env.clone().block.height,
-            price_paid: bond.price_paid
-        };
-        BOND.save(deps.storage, &recipient, &bond_info_to_save)?;
-        return stake_or_send(
-            info.clone(), 
-            state.staking.to_string(), 
-            treasury_address, 
-            recipient, 
-            stake, 
-            payout
-        );
-    }
-}
-
-fn stake_or_send( 
-    info: MessageInfo, 
-    staking_address: String, 
-    treasury_address: String, 
-    recipient: Addr, 
-    stake: bool, 
-    payout: u64) -> Result<Response, ContractError>{
+            payout: bond.payout - scheduled,
+            claimable: available - payout,
+            vesting: total - vested,
+            last_block,
+            last_time,
+            ..bond.clone()
+        };
+        bonds().replace(storage, U64Key::new(bond_id), Some(&bond_info_to_save), Some(&bond))?;
+        Ok((bond_info_to_save, payout))
+    }
+}
 
-    let mut response = Response::new();
+pub fn early_exit(mut deps: DepsMut, env: Env, info: MessageInfo, bond_id: u64) -> Result<Response, ContractError> {
+    let pause_status = PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default();
+    if pause_status.redemptions {
+        return Err(ContractError::Paused{})
+    }
+    let terms = EARLY_EXIT.may_load(deps.storage)?.ok_or(ContractError::EarlyExitDisabled{})?;
+    let bond = bonds().may_load(deps.storage, U64Key::new(bond_id))?.ok_or(ContractError::NoBond{})?;
+    if bond.owner != info.sender {
+        return Err(ContractError::Unauthorized{})
+    }
+    let exit = early_exit_amounts(&bond, &env, &terms);
+    if exit.payout == 0 {
+        return Err(ContractError::NothingToRedeem{})
+    }
 
+    bonds().replace(deps.storage, U64Key::new(bond_id), None, Some(&bond))?;
 
-    // response
-    // .messages
-    // .push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-    //     funds: vec![],
-    //     contract_addr: treasury_address,
-    //     msg: to_binary(&Cw20ExecuteMsg::Transfer {
-    //         recipient: recipient.to_string(),
-    //         amount: Uint128::from(payout)
-    //     })?,
-    // })));
-
-    // if stake{
-    //     response    
-    //     .messages
-    //     .push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-    //         funds: vec![],
-    //         contract_addr: staking_address.clone(),
-    //         msg: to_binary(&StakingExecuteMsg::Stake {
-    //             owner: info.sender.to_string(),
-    //             recipient: staking_address,
-    //             amount: payout
-    //         })?,
-    //     }))); 
-
-    // }
-  
+    let state = STATE.load(deps.storage)?;
+    let mut response = pay_out(&state, &bond, false, exit.payout)?
+        .add_attribute("early_exit", "true")
+        .add_attribute("bond_id", bond_id.to_string())
+        .add_attribute("penalty", exit.penalty.to_string());
+    // Staked shares cannot be burned, a wrapped penalty always goes to the DAO.
+    // Fees are counted in PHS, so only PHS penalties are added to them.
+    if exit.penalty > 0 && bond.wrapped {
+        response = response.add_submessage(share_transfer(&state, &state.dao, exit.penalty)?);
+    } else if exit.penalty > 0 {
+        response = response
+            .add_submessages(fee_messages(&state, &terms.destination, exit.penalty)?)
+            .add_event(collect_fee(deps.branch(), &terms.destination, exit.penalty)?);
+    }
     Ok(response)
 }
 
+// The vested part is paid in full, only what has not vested yet is penalised
+fn early_exit_amounts(bond: &Bond, env: &Env, terms: &EarlyExitTerms) -> EarlyExitResponse {
+    let percent_vested = percent_vested(bond, env);
+    let scheduled = bond.payout * percent_vested.min(10000) / 10000;
+    let vested = bond.claimable + scheduled;
+    let unvested = bond.payout - scheduled;
+    let penalty = unvested * terms.penalty / 10000;
+    EarlyExitResponse{
+        vested,
+        unvested,
+        penalty,
+        payout: vested + unvested - penalty
+    }
+}
+
+// Wrapped positions hold staked shares, which are wrapped into wsPHS for the owner
+fn pay_out(state: &State, bond: &Bond, stake: bool, payout: u64) -> Result<Response, ContractError>{
+    if !bond.wrapped {
+        return stake_or_send(state, &bond.owner, stake, payout)
+    }
+    Ok(Response::new()
+        .add_submessage(wrap_shares(state, &bond.owner, payout)?)
+        .add_attribute("method", "redeem")
+        .add_attribute("recipient", &bond.owner)
+        .add_attribute("payout", payout.to_string())
+        .add_attribute("staked", "true"))
+}
+
+fn stake_or_send(state: &State, recipient: &Addr, stake: bool, payout: u64) -> Result<Response, ContractError>{
+    let amount = Uint128::from(payout);
+    let response = if stake {
+        Response::new().add_submessages(stake_messages(state, recipient, payout)?)
+    } else {
+        Response::new()
+            .add_submessage(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute{
+                funds: vec![],
+                contract_addr: state.treasury.to_string(),
+                msg: to_binary(&TreasuryExecuteMsg::Transfer{
+                    recipient: recipient.to_string(),
+                    amount
+                })?
+            })))
+    };
+    Ok(response
+        .add_attribute("method", "redeem")
+        .add_attribute("recipient", recipient)
+        .add_attribute("payout", payout.to_string())
+        .add_attribute("staked", stake.to_string()))
+}
+
 
+// Staking pulls the PHS from the bond, so staking first raises the staking contract's allowance
+fn stake_messages(state: &State, recipient: &Addr, amount: u64) -> StdResult<Vec<SubMsg>> {
+    let amount = Uint128::from(amount);
+    Ok(vec![
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute{
+            funds: vec![],
+            contract_addr: state.treasury.to_string(),
+            msg: to_binary(&TreasuryExecuteMsg::IncreaseAllowance{
+                spender: state.staking.to_string(),
+                amount
+            })?
+        })),
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute{
+            funds: vec![],
+            contract_addr: state.staking.to_string(),
+            msg: to_binary(&StakingExecuteMsg::Stake{
+                recipient: recipient.to_string(),
+                amount
+            })?
+        }))
+    ])
+}
+
+fn share_transfer(state: &State, recipient: &Addr, shares: u64) -> StdResult<SubMsg> {
+    Ok(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute{
+        funds: vec![],
+        contract_addr: state.staking.to_string(),
+        msg: to_binary(&StakingExecuteMsg::TransferShares{
+            recipient: recipient.to_string(),
+            shares: Uint128::from(shares)
+        })?
+    })))
+}
+
+// The staking contract moves the shares to wsPHS, which mints the same amount to the recipient
+fn wrap_shares(state: &State, recipient: &Addr, shares: u64) -> StdResult<SubMsg> {
+    let wrapped_token = state.wrapped_token.as_ref()
+        .ok_or_else(|| StdError::generic_err("wsPHS token not set"))?;
+    Ok(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute{
+        funds: vec![],
+        contract_addr: state.staking.to_string(),
+        msg: to_binary(&StakingExecuteMsg::SendShares{
+            contract: wrapped_token.to_string(),
+            shares: Uint128::from(shares),
+            msg: to_binary(&WrapMsg::Wrap{ recipient: Some(recipient.to_string()) })?
+        })?
+    })))
+}
+
+// Shares the staking contract mints for a stake of `amount` PHS in this block
+fn shares_for(deps: Deps, state: &State, amount: u64) -> StdResult<u64> {
+    let res: SharesForResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart{
+        contract_addr: state.staking.to_string(),
+        msg: to_binary(&StakingQueryMsg::SharesFor{ amount: Uint128::from(amount) })?
+    }))?;
+    u64::try_from(res.shares.u128()).map_err(|_| StdError::generic_err("shares overflow"))
+}
 
 pub fn set_adjustment(
-    mut deps: DepsMut, 
-    info: MessageInfo, 
-    env: Env, 
-    addition: bool, 
-    increment: u64, 
-    target: u64, 
-    buffer: u64 
+    mut deps: DepsMut,
+    info: MessageInfo,
+    market_id: u64,
+    adjust: Adjust
 ) -> Result<Response, ContractError> {
     only_admin(deps.branch(), info)?;
-    let terms = TERMS.load(deps.branch().storage)?;
-    let compare = terms.control_variable * 25 / 1000;
-    if increment > compare {
+    assert_no_timelock(deps.as_ref())?;
+    apply_adjustment(deps, market_id, adjust)?;
+    Ok(Response::default())
+}
+
+// Adjustments run from the block they are set in
+fn new_adjust(env: &Env, addition: bool, increment: u64, target: u64, buffer: u64) -> Adjust {
+    Adjust{
+        add: addition,
+        rate: increment,
+        target,
+        buffer,
+        last_block: env.block.height,
+        last_time: env.block.time.seconds()
+    }
+}
+
+fn apply_adjustment(deps: DepsMut, market_id: u64, adjust: Adjust) -> Result<(), ContractError> {
+    let mut market = load_market(deps.storage, market_id)?;
+    let compare = market.terms.control_variable * 25 / 1000;
+    if adjust.rate > compare {
         return Err(ContractError::LargeIncrement{})
     }
-    ADJUST.update(deps.storage, |mut adjust| -> StdResult<_>{
-        adjust.add = addition;
-        adjust.rate = increment;
-        adjust.target = target;
-        adjust.buffer = buffer;
-        adjust.last_block = env.block.height;
-        Ok(adjust)
-    })?;
-    Ok(Response::default())
+    market.adjust = Some(adjust);
+    MARKETS.save(deps.storage, U64Key::new(market_id), &market)?;
+    Ok(())
+}
+
+fn load_open_market(storage: &dyn Storage, market_id: u64) -> Result<Market, ContractError> {
+    let market = load_market(storage, market_id)?;
+    if market.closed {
+        return Err(ContractError::MarketClosed{})
+    }
+    Ok(market)
+}
+
+fn load_market(storage: &dyn Storage, market_id: u64) -> Result<Market, ContractError> {
+    match MARKETS.may_load(storage, U64Key::new(market_id))? {
+        Some(market) => Ok(market),
+        // The default market only exists once Init has run
+        None if market_id == DEFAULT_MARKET => Err(ContractError::NotInitialized{}),
+        None => Err(ContractError::UnknownMarket{ market_id })
+    }
+}
+
+fn assert_no_timelock(deps: Deps) -> Result<(), ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if state.timelock > 0 {
+        return Err(ContractError::TimelockActive{})
+    }
+    Ok(())
+}
+
+// Queues a parameter change, it can be executed by anyone once the timelock has passed
+pub fn propose_change(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    change: ParamChange
+) -> Result<Response, ContractError> {
+    only_admin(deps.branch(), info)?;
+    validate_change(deps.as_ref(), &env, &change)?;
+
+    let state = STATE.load(deps.storage)?;
+    let id = CHANGE_COUNT.may_load(deps.storage)?.unwrap_or(0);
+    let executable_at = env.block.height + state.timelock;
+    PENDING_CHANGES.save(deps.storage, U64Key::new(id), &PendingChange{ change, executable_at })?;
+    CHANGE_COUNT.save(deps.storage, &(id + 1))?;
+
+    Ok(Response::new()
+        .add_attribute("method", "propose_change")
+        .add_attribute("change_id", id.to_string())
+        .add_attribute("executable_at", executable_at.to_string()))
+}
+
+pub fn execute_change(deps: DepsMut, env: Env, id: u64) -> Result<Response, ContractError> {
+    let pending = PENDING_CHANGES.load(deps.storage, U64Key::new(id))?;
+    if env.block.height < pending.executable_at {
+        return Err(ContractError::ChangeLocked{ executable_at: pending.executable_at })
+    }
+    // Rules or the market may have changed while the change was queued
+    validate_change(deps.as_ref(), &env, &pending.change)?;
+    PENDING_CHANGES.remove(deps.storage, U64Key::new(id));
+
+    match pending.change {
+        ParamChange::Terms{ market_id, terms } => {
+            let mut market = load_market(deps.storage, market_id)?;
+            market.auction = open_auction(&terms.pricing);
+            market.terms = terms;
+            MARKETS.save(deps.storage, U64Key::new(market_id), &market)?;
+        },
+        ParamChange::BondTerm{ market_id, parameter, value } => {
+            apply_bond_term(deps, market_id, parameter, value)?;
+        },
+        ParamChange::Adjustment{ market_id, addition, increment, target, buffer } => {
+            apply_adjustment(deps, market_id, new_adjust(&env, addition, increment, target, buffer))?;
+        },
+        ParamChange::Staking{ staking } => {
+            let staking = deps.api.addr_validate(&staking)?;
+            STATE.update(deps.storage, |mut state| -> StdResult<_>{
+                state.staking = staking;
+                Ok(state)
+            })?;
+        },
+        ParamChange::WrappedToken{ address } => {
+            let address = deps.api.addr_validate(&address)?;
+            STATE.update(deps.storage, |mut state| -> StdResult<_>{
+                state.wrapped_token = Some(address);
+                Ok(state)
+            })?;
+        },
+        ParamChange::Timelock{ blocks } => {
+            STATE.update(deps.storage, |mut state| -> StdResult<_>{
+                state.timelock = blocks;
+                Ok(state)
+            })?;
+        },
+        ParamChange::EarlyExit{ penalty, destination } => {
+            EARLY_EXIT.save(deps.storage, &EarlyExitTerms{ penalty, destination })?;
+        },
+        ParamChange::FeeDestination{ destination } => {
+            STATE.update(deps.storage, |mut state| -> StdResult<_>{
+                state.fee_destination = destination;
+                Ok(state)
+            })?;
+        },
+        ParamChange::RedeemBatchLimit{ limit } => {
+            REDEEM_BATCH_LIMIT.save(deps.storage, &limit)?;
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_change")
+        .add_attribute("change_id", id.to_string()))
+}
+
+pub fn cancel_change(mut deps: DepsMut, info: MessageInfo, id: u64) -> Result<Response, ContractError> {
+    only_admin(deps.branch(), info)?;
+    PENDING_CHANGES.load(deps.storage, U64Key::new(id))?;
+    PENDING_CHANGES.remove(deps.storage, U64Key::new(id));
+    Ok(Response::new()
+        .add_attribute("method", "cancel_change")
+        .add_attribute("change_id", id.to_string()))
+}
+
+fn validate_change(deps: Deps, env: &Env, change: &ParamChange) -> Result<(), ContractError> {
+    match change {
+        ParamChange::Adjustment{ market_id, increment, .. } => {
+            let market = load_open_market(deps.storage, *market_id)?;
+            if *increment > market.terms.control_variable * 25 / 1000 {
+                return Err(ContractError::LargeIncrement{})
+            }
+        },
+        ParamChange::Staking{ staking } => {
+            deps.api.addr_validate(staking)?;
+        },
+        ParamChange::WrappedToken{ address } => {
+            deps.api.addr_validate(address)?;
+        },
+        ParamChange::BondTerm{ market_id, parameter, value } => {
+            let market = load_open_market(deps.storage, *market_id)?;
+            validate_bond_term(&market.terms.vesting_unit, parameter, *value)?;
+        },
+        ParamChange::Terms{ market_id, terms } => {
+            let market = load_open_market(deps.storage, *market_id)?;
+            // Open positions are counted in the market's payout token
+            if terms.wrapped_payout != market.terms.wrapped_payout {
+                return Err(ContractError::InvalidTerms{ reason: "payout token cannot change".to_string() })
+            }
+            validate_pricing(&terms.pricing)?;
+            validate_expiry(env, terms.fixed_expiry)?;
+            validate_schedule(terms)?;
+        },
+        ParamChange::Timelock{ .. } => {},
+        ParamChange::EarlyExit{ penalty, .. } => {
+            validate_early_exit(*penalty)?;
+        },
+        ParamChange::FeeDestination{ .. } | ParamChange::RedeemBatchLimit{ .. } => {}
+    }
+    Ok(())
 }
 
 pub fn deposit(
     mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    market_id: u64,
     max_price: u64
 ) ->Result<Response, ContractError>{
     
-    decay_debt(deps.branch(), env.clone());
+    let pause_status = PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default();
+    if pause_status.deposits {
+        return Err(ContractError::Paused{})
+    }
 
-    let mut state = STATE.load(deps.storage)?;
-    let terms = TERMS.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
+    let mut market = load_market(deps.storage, market_id)?;
+    if market.closed {
+        return Err(ContractError::MarketClosed{})
+    }
+    // The exemption can be revoked after the market opened, shares staked in warmup could not be paid out
+    if market.terms.wrapped_payout && !warmup_exempt(deps.as_ref(), &env, &state)? {
+        return Err(ContractError::NotWarmupExempt{})
+    }
 
-    if state.total_debt >= terms.max_debt{
+    // Fixed-expiry bonds all mature together, late deposits only vest for what is left
+    let vesting = match market.terms.fixed_expiry {
+        Some(expiry) if env.block.time.seconds() >= expiry => return Err(ContractError::MarketClosed{}),
+        Some(expiry) => expiry - env.block.time.seconds(),
+        None => market.terms.vesting_term
+    };
+
+    decay_debt(&mut market, env.clone());
+
+    if market.total_debt >= market.terms.max_debt{
         return Err(ContractError:: MaxCapacity{})
     }
 
-    let price_in_usd = bond_price_in_usd(deps.as_ref(), env.clone())?;
-    let native_price = _bond_price(deps.branch(), env.clone())?;
+    if let PricingModel::SequentialDutchAuction(auction_terms) = &market.terms.pricing {
+        let time = env.block.time.seconds();
+        match market.auction.as_mut() {
+            Some(auction) if auction_terms.is_active(auction, time) => auction.decay(auction_terms, time),
+            _ => return Err(ContractError::MarketClosed{})
+        }
+    }
+
+    let price_in_usd = bond_price_in_usd(deps.as_ref(), env.clone(), &market)?;
+    let native_price = _bond_price(deps.as_ref(), env.clone(), &mut market)?;
 
     if max_price <= native_price {
         return Err(ContractError::SlippageLimit{})
@@ -197,47 +652,89 @@ pub fn deposit(
     let deposit_amount = info
                             .funds
                             .iter()
-                            .find(|c| c.denom == "uusd")
+                            .find(|c| c.denom == market.quote_denom)
                             .map(|c| c.amount)
                             .unwrap_or_else(Uint128::zero);
 
     let value = deposit_amount.u128() as u64 * 10u64.pow(3);
 
-    let payout = payout_for(deps.branch(), env.clone(), value)? * 100;
+    let payout = payout_for(deps.as_ref(), env.clone(), &market, value)? * 100;
 
     if payout <= 10000000u64 {
         return Err(ContractError::SmallBond{})
     }
 
-    let max_payout = max_payout(deps.as_ref(), String::from(state.clone().treasury), terms.max_payout)?;
+    let max_payout = max_payout(deps.as_ref(), String::from(state.clone().treasury), market.terms.max_payout)?;
 
     if payout >= max_payout{
         return Err(ContractError::LargeBond{})
     }
 
-    let fee = payout * terms.fee / 100000u64;
+    // Auctions pace their sales, each deposit is capped to one deposit interval of capacity
+    if let PricingModel::SequentialDutchAuction(auction_terms) = &market.terms.pricing {
+        if let Some(auction) = market.auction.as_mut() {
+            if payout > auction_terms.max_payout() {
+                return Err(ContractError::LargeBond{})
+            }
+            if payout > auction.capacity {
+                return Err(ContractError::MaxCapacity{})
+            }
+            auction.purchase(payout, value);
+        }
+    }
+
+    let fee = payout * market.terms.fee / 100000u64;
     let profit = value - payout - fee;
 
-    state.total_debt = state.total_debt + value;
-    STATE.save(deps.branch().storage, &state)?;
+    market.total_debt += value;
+
+    // Wrapped markets stake the payout here and owe the depositor the shares it mints
+    let owed = if market.terms.wrapped_payout {
+        shares_for(deps.as_ref(), &state, payout)?
+    } else {
+        payout
+    };
 
     let depositor = info.sender;
-    let bond_info = bond_info(deps.as_ref(), depositor.clone().to_string())?;
+    let previous = bond_info(deps.as_ref(), depositor.to_string(), market_id)?;
+    let (payout_so_far, claimable) = previous.as_ref().map_or((0, 0), |bond| (bond.payout, bond.claimable));
+    let bond_id = match bonds().idx.owner.item(deps.storage, (depositor.clone(), U64Key::new(market_id)))? {
+        Some((key, _)) => bond_id(&key),
+        None => {
+            let bond_id = BOND_COUNT.may_load(deps.storage)?.unwrap_or(0);
+            BOND_COUNT.save(deps.storage, &(bond_id + 1))?;
+            bond_id
+        }
+    };
+    let cliff_start = match (market.terms.fixed_expiry, &market.terms.vesting_unit) {
+        (None, VestingUnit::Blocks) => env.block.height,
+        _ => env.block.time.seconds()
+    };
     let bond_info_to_save = Bond{
-        payout: bond_info.payout + payout,
-        vesting: terms.vesting_term,
+        owner: depositor.clone(),
+        market_id,
+        payout: payout_so_far + owed,
+        claimable,
+        vesting,
         last_block: env.block.height,
-        price_paid: price_in_usd
+        price_paid: price_in_usd,
+        last_time: env.block.time.seconds(),
+        expiry: market.terms.fixed_expiry,
+        vesting_unit: market.terms.vesting_unit.clone(),
+        cliff_end: cliff_start + market.terms.cliff,
+        curve: market.terms.curve.clone(),
+        wrapped: market.terms.wrapped_payout
     };
-    BOND.save(deps.branch().storage, &depositor, &bond_info_to_save)?;
+    bonds().replace(deps.storage, U64Key::new(bond_id), Some(&bond_info_to_save), previous.as_ref())?;
 
-    adjust(deps.branch(), env.clone());
+    adjust(&mut market, env.clone());
+    MARKETS.save(deps.storage, U64Key::new(market_id), &market)?;
 
     //let coin = deduct_tax(deps.as_ref(), Coin::new(deposit_amount.u128(),"uusd"))?;
-    let coin = Coin::new(deposit_amount.u128(), "uusd");
+    let coin = Coin::new(deposit_amount.u128(), market.quote_denom);
     
 
-    Ok(Response::new()
+    let mut response = Response::new()
                     .add_submessage(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute{
                         funds: vec![coin],
                         contract_addr: String::from(state.clone().treasury),
@@ -245,36 +742,105 @@ pub fn deposit(
                             amount: value,
                             profit
                         })?
-                    })))
-    )
-    //TODO: Send to treasury 
+                    })));
+
+    if market.terms.wrapped_payout {
+        response = response.add_submessages(stake_messages(&state, &env.contract.address, payout)?);
+    }
+
+    // The treasury mints payout + fee to this contract, route the fee part onwards
+    if fee > 0 {
+        response = response
+                    .add_submessages(fee_messages(&state, &state.fee_destination, fee)?)
+                    .add_event(collect_fee(deps.branch(), &state.fee_destination, fee)?);
+    }
+
+    Ok(response.add_attribute("market_id", market_id.to_string())
+                .add_attribute("bond_id", bond_id.to_string()))
+}
+
+// Staking fees are handed in as rewards, so like staking they go through an allowance first
+fn fee_messages(state: &State, destination: &FeeDestination, fee: u64) -> StdResult<Vec<SubMsg>> {
+    let amount = Uint128::from(fee);
+    let msg = match destination {
+        FeeDestination::Dao => TreasuryExecuteMsg::Transfer{
+            recipient: state.dao.to_string(),
+            amount
+        },
+        FeeDestination::Staking => TreasuryExecuteMsg::IncreaseAllowance{
+            spender: state.staking.to_string(),
+            amount
+        },
+        FeeDestination::Burn => TreasuryExecuteMsg::Burn{
+            amount
+        }
+    };
+    let mut messages = vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute{
+        funds: vec![],
+        contract_addr: state.treasury.to_string(),
+        msg: to_binary(&msg)?
+    }))];
+    if let FeeDestination::Staking = destination {
+        messages.push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute{
+            funds: vec![],
+            contract_addr: state.staking.to_string(),
+            msg: to_binary(&StakingExecuteMsg::AddRewards{
+                amount
+            })?
+        })));
+    }
+    Ok(messages)
+}
+
+fn collect_fee(deps: DepsMut, destination: &FeeDestination, fee: u64) -> StdResult<Event> {
+    let mut fees = FEES_COLLECTED.may_load(deps.storage)?.unwrap_or_default();
+    let destination = match destination {
+        FeeDestination::Dao => {
+            fees.dao += fee;
+            "dao"
+        },
+        FeeDestination::Burn => {
+            fees.burned += fee;
+            "burn"
+        },
+        FeeDestination::Staking => {
+            fees.staking += fee;
+            "staking"
+        }
+    };
+    FEES_COLLECTED.save(deps.storage, &fees)?;
+    Ok(Event::new("bond_fee")
+        .add_attribute("destination", destination)
+        .add_attribute("amount", fee.to_string()))
 }
 
-fn adjust(deps: DepsMut, env: Env){
-    let adjust_info = ADJUST.load(deps.storage);
-    let mut adjust = match adjust_info {
-        Ok(_) => adjust_info.unwrap(),
-        Err(_) => Adjust{add: false, rate:0, target: 0, buffer:0, last_block:0 }
+fn adjust(market: &mut Market, env: Env){
+    let mut adjust = match market.adjust.clone() {
+        Some(adjust) => adjust,
+        None => Adjust{add: false, rate:0, target: 0, buffer:0, last_block:0, last_time: 0 }
     };
     if adjust.last_block != 0 {
-        let block_can_adjust = adjust.last_block + adjust.buffer;
-        if adjust.rate != 0 && env.block.height >= block_can_adjust{
-            let mut terms = TERMS.load(deps.storage).unwrap();
-            let initial = terms.control_variable;
+        let (now, last_adjust) = match market.terms.vesting_unit {
+            VestingUnit::Blocks => (env.block.height, adjust.last_block),
+            VestingUnit::Seconds => (env.block.time.seconds(), adjust.last_time)
+        };
+        let block_can_adjust = last_adjust + adjust.buffer;
+        if adjust.rate != 0 && now >= block_can_adjust{
+            let initial = market.terms.control_variable;
             if adjust.add {
-                terms.control_variable = initial + adjust.target;
-                if terms.control_variable >= adjust.target {
+                market.terms.control_variable = initial + adjust.target;
+                if market.terms.control_variable >= adjust.target {
                     adjust.rate = 0;
                 }
             }else{
-                terms.control_variable = initial - adjust.target;
-                if terms.control_variable <= adjust.target {
+                market.terms.control_variable = initial - adjust.target;
+                if market.terms.control_variable <= adjust.target {
                     adjust.rate = 0;
                 } 
             }
             adjust.last_block = env.block.height;
-            let _terms = TERMS.save(deps.storage, &terms);
-            let _adjust = ADJUST.save(deps.storage, &adjust);
+            adjust.last_time = env.block.time.seconds();
+            market.adjust = Some(adjust);
         }
     }
 
@@ -313,50 +879,45 @@ fn max_payout(deps: Deps, addr: String, max_pay: u64) -> StdResult<u64>{
     Ok(total_supply * max_pay / 100u64)
 }
 
-fn payout_for(deps: DepsMut, env: Env, value: u64) -> StdResult<u64> {
-    Ok( value / bond_price(deps.as_ref(), env)?)
+fn payout_for(deps: Deps, env: Env, market: &Market, value: u64) -> StdResult<u64> {
+    Ok( value / bond_price(deps, env, market)?)
 }
 
-fn _bond_price(deps: DepsMut, env: Env) -> StdResult<u64>{
-    let state = STATE.load(deps.storage)?;
-    let mut terms = TERMS.load(deps.storage)?;
-    let debt_ratio = debt_ratio(deps.as_ref(), env, String::from(state.treasury))?;
-    let mut price = (terms.control_variable *  debt_ratio + 1000000000u64) / 10u64.pow(7);
-    if price < terms.minimum_price {
-        price = terms.minimum_price;
-    }else if terms.minimum_price != 0 {
-        terms.minimum_price = 500; //TODO: Figure out minimum price
-    }
-    let _res = TERMS.save(deps.storage, &terms);
+fn _bond_price(deps: Deps, env: Env, market: &mut Market) -> StdResult<u64>{
+    let price = bond_price(deps, env, market)?;
+    if let PricingModel::ControlVariable(_) = market.terms.pricing {
+        if price > market.terms.minimum_price && market.terms.minimum_price != 0 {
+            market.terms.minimum_price = 500; //TODO: Figure out minimum price
+        }
+    }
     Ok(price)
 }
 
-fn bond_price_in_usd(deps: Deps, env: Env) -> StdResult<u64> {
-    Ok(bond_price(deps,env)? * 10u64.pow(6) / 100)
+fn bond_price_in_usd(deps: Deps, env: Env, market: &Market) -> StdResult<u64> {
+    Ok(bond_price(deps, env, market)? * 10u64.pow(6) / 100)
 }
 
-fn bond_price(deps: Deps, env: Env) -> StdResult<u64> {
+fn bond_price(deps: Deps, env: Env, market: &Market) -> StdResult<u64> {
     let state = STATE.load(deps.storage)?;
-    let terms = TERMS.load(deps.storage)?;
-    let debt_ratio = debt_ratio(deps, env, String::from(state.treasury))?;
-    
-    let mut price = (terms.control_variable * debt_ratio + 1000000000u64) / 10u64.pow(7);
-    
-    if price < terms.minimum_price {
-        price = terms.minimum_price;
-    }
-    Ok(price)
+    let inputs = PriceInputs{
+        control_variable: market.terms.control_variable,
+        debt_ratio: debt_ratio(deps, env.clone(), market, String::from(state.treasury))?,
+        minimum_price: market.terms.minimum_price,
+        block_height: env.block.height,
+        block_time: env.block.time.seconds(),
+        auction: market.auction.clone()
+    };
+    Ok(market.terms.pricing.model().price(&inputs))
 }
 
-fn debt_ratio(deps: Deps, env: Env, addr:String) -> StdResult<u64> {
+fn debt_ratio(deps: Deps, env: Env, market: &Market, addr:String) -> StdResult<u64> {
     let total_supply = total_supply(deps, addr)?;
-    let current_debt = current_debt(deps, env)?;
+    let current_debt = current_debt(market, env);
     Ok(current_debt * 10u64.pow(9) / total_supply)
 }
 
-fn current_debt(deps: Deps, env: Env) -> StdResult<u64>{
-    let state = STATE.load(deps.storage)?;
-    Ok(state.total_debt - debt_decay(deps, env)?)
+fn current_debt(market: &Market, env: Env) -> u64{
+    market.total_debt - debt_decay(market, env)
 }
 
 fn total_supply(deps: Deps, addr: String) -> StdResult<u64> {
@@ -368,23 +929,106 @@ fn total_supply(deps: Deps, addr: String) -> StdResult<u64> {
     Ok(60000000000)
 }
 
-fn decay_debt(deps: DepsMut, env: Env){
-    let mut state = STATE.load(deps.storage).unwrap();
-    state.total_debt = state.total_debt - debt_decay(deps.as_ref(), env.clone()).unwrap();
-    state.last_decay = env.block.height;
-    let _res = STATE.save(deps.storage, &state);
+fn decay_debt(market: &mut Market, env: Env){
+    market.total_debt -= debt_decay(market, env.clone());
+    market.last_decay = env.block.height;
+    market.last_decay_time = env.block.time.seconds();
 }
 
-fn debt_decay(deps: Deps, env: Env) -> StdResult<u64> {
-    let state = STATE.load(deps.storage)?;
-    let last_decay = state.last_decay;
-    let height_since_last = env.block.height - last_decay;
-    let total_debt = state.total_debt;
-    let mut decay = total_debt *height_since_last;
+// Debt runs off linearly over one vesting term, counted in the market's vesting unit
+fn debt_decay(market: &Market, env: Env) -> u64 {
+    let total_debt = market.total_debt;
+    let since_last = match market.terms.vesting_unit {
+        VestingUnit::Blocks => env.block.height - market.last_decay,
+        VestingUnit::Seconds => env.block.time.seconds() - market.last_decay_time
+    };
+    let mut decay = total_debt * since_last / market.terms.vesting_term.max(1);
     if decay > total_debt {
         decay = total_debt;
     }
-    Ok(decay)
+    decay
+}
+
+pub fn set_bond_terms(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    market_id: u64,
+    parameter: BondParameter,
+    value: u64
+) -> Result<Response, ContractError>{
+    only_admin(deps.branch(), info)?;
+    assert_no_timelock(deps.as_ref())?;
+    apply_bond_term(deps, market_id, parameter.clone(), value)?;
+    Ok(Response::new()
+        .add_attribute("method", "set_bond_terms")
+        .add_attribute("market_id", market_id.to_string())
+        .add_attribute("parameter", format!("{:?}", parameter))
+        .add_attribute("value", value.to_string()))
+}
+
+// Changes a single term in place, unlike Init this leaves the debt accounting alone
+fn apply_bond_term(deps: DepsMut, market_id: u64, parameter: BondParameter, value: u64) -> Result<(), ContractError>{
+    let mut market = load_market(deps.storage, market_id)?;
+    validate_bond_term(&market.terms.vesting_unit, &parameter, value)?;
+    let terms = &mut market.terms;
+    match parameter {
+        BondParameter::Vesting => terms.vesting_term = value,
+        BondParameter::MaxPayout => terms.max_payout = value,
+        BondParameter::Fee => terms.fee = value,
+        BondParameter::Debt => terms.max_debt = value,
+        BondParameter::MinimumPrice => terms.minimum_price = value
+    }
+    MARKETS.save(deps.storage, U64Key::new(market_id), &market)?;
+    Ok(())
+}
+
+// New auction terms restart the auction, other pricing models carry no auction state
+fn open_auction(pricing: &PricingModel) -> Option<AuctionState>{
+    match pricing {
+        PricingModel::SequentialDutchAuction(auction_terms) => Some(auction_terms.open()),
+        _ => None
+    }
+}
+
+fn validate_pricing(pricing: &PricingModel) -> Result<(), ContractError>{
+    match pricing.model().validate() {
+        Ok(()) => Ok(()),
+        Err(reason) => Err(ContractError::InvalidTerms{ reason })
+    }
+}
+
+fn validate_expiry(env: &Env, fixed_expiry: Option<u64>) -> Result<(), ContractError>{
+    match fixed_expiry {
+        Some(expiry) if expiry <= env.block.time.seconds() =>
+            Err(ContractError::InvalidTerms{ reason: "fixed expiry must be in the future".to_string() }),
+        _ => Ok(())
+    }
+}
+
+fn validate_schedule(terms: &Terms) -> Result<(), ContractError>{
+    if terms.fixed_expiry.is_none() && terms.cliff > terms.vesting_term {
+        return Err(ContractError::InvalidTerms{ reason: "cliff cannot exceed the vesting term".to_string() })
+    }
+    match terms.curve {
+        VestingCurve::Step{ interval: 0 } =>
+            Err(ContractError::InvalidTerms{ reason: "step interval must be positive".to_string() }),
+        _ => Ok(())
+    }
+}
+
+fn validate_bond_term(vesting_unit: &VestingUnit, parameter: &BondParameter, value: u64) -> Result<(), ContractError>{
+    let reason = match (parameter, vesting_unit) {
+        (BondParameter::Vesting, VestingUnit::Blocks) if value < MIN_VESTING_TERM =>
+            format!("vesting must be at least {} blocks", MIN_VESTING_TERM),
+        (BondParameter::Vesting, VestingUnit::Seconds) if value < MIN_VESTING_SECONDS =>
+            format!("vesting must be at least {} seconds", MIN_VESTING_SECONDS),
+        (BondParameter::MaxPayout, _) if value > MAX_PAYOUT_LIMIT =>
+            format!("max payout cannot exceed {}% of supply", MAX_PAYOUT_LIMIT),
+        (BondParameter::Fee, _) if value > MAX_FEE =>
+            "fee cannot exceed 10% of payout".to_string(),
+        _ => return Ok(())
+    };
+    Err(ContractError::InvalidTerms{ reason })
 }
 
 pub fn set_staking(
@@ -393,124 +1037,897 @@ pub fn set_staking(
     staking: String
 ) -> Result<Response, ContractError>{
     only_admin(deps.branch(), info)?;
+    assert_no_timelock(deps.as_ref())?;
     let mut state = STATE.load(deps.storage)?;
     state.staking =  deps.api.addr_validate(&staking)?;
     STATE.save(deps.storage, &state)?; 
     Ok(Response::default())
 }
 
-pub fn initialize_bond_terms(
+pub fn set_wrapped_token(
     mut deps: DepsMut,
-    env: Env,
     info: MessageInfo,
-    control_variable: u64,
-    vesting_term: u64,
-    minimum_price: u64,
-    max_payout: u64,
-    fee: u64,
-    max_debt: u64,
-    initial_debt: u64
-) -> Result<Response, ContractError> {
+    address: String
+) -> Result<Response, ContractError>{
     only_admin(deps.branch(), info)?;
-
-    let terms_data = Terms{
-        control_variable,
-        vesting_term,
-        minimum_price,
-        max_payout,
-        fee,
-        max_debt
-    };    
-    TERMS.save(deps.storage, &terms_data)?;
-
+    assert_no_timelock(deps.as_ref())?;
+    let address = deps.api.addr_validate(&address)?;
     STATE.update(deps.storage, |mut state| -> StdResult<_>{
-        state.total_debt = initial_debt;
-        state.last_decay = env.block.height;
+        state.wrapped_token = Some(address);
         Ok(state)
     })?;
-
     Ok(Response::default())
 }
 
-fn only_admin(deps: DepsMut, info: MessageInfo) -> Result< (),ContractError>{
-    let res = BOND_ADMIN.assert_admin(deps.as_ref(), &info.sender);
-    match res{
-        Ok(()) => return Ok(()),
-        Err(_not_admin) => return Err(ContractError::Unauthorized{})
-    }; 
+pub fn set_fee_destination(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    destination: FeeDestination
+) -> Result<Response, ContractError>{
+    only_admin(deps.branch(), info)?;
+    assert_no_timelock(deps.as_ref())?;
+    STATE.update(deps.storage, |mut state| -> StdResult<_>{
+        state.fee_destination = destination;
+        Ok(state)
+    })?;
+    Ok(Response::default())
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::BondInfo{address} => to_binary(&bond_info(deps, address)?),
-        QueryMsg::BondPriceInUsd{} => to_binary(&bond_price_in_usd(deps, _env)?),
-        QueryMsg::MaxPayout{address, max_pay} => to_binary(&max_payout(deps, address, max_pay)?),
-        QueryMsg::PendingPayout{address} => to_binary(&pending_payout(deps, _env, address)?)
-    
-    }
+pub fn set_early_exit(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    penalty: u64,
+    destination: FeeDestination
+) -> Result<Response, ContractError>{
+    only_admin(deps.branch(), info)?;
+    assert_no_timelock(deps.as_ref())?;
+    validate_early_exit(penalty)?;
+    EARLY_EXIT.save(deps.storage, &EarlyExitTerms{ penalty, destination })?;
+    Ok(Response::default())
 }
 
-fn bond_info(deps: Deps, depositor: String) -> StdResult<Bond> {
-    let mut bond_info = BOND.load(deps.storage, &deps.api.addr_validate(&depositor).unwrap());
-    match bond_info.as_mut(){
-        Ok(_bond) => Ok(bond_info.unwrap()),
-        Err(_) => return Ok(Bond{payout: 0,vesting: 0, last_block:0, price_paid:0 })
+fn validate_early_exit(penalty: u64) -> Result<(), ContractError>{
+    if penalty > MAX_EARLY_EXIT_PENALTY {
+        return Err(ContractError::InvalidTerms{
+            reason: format!("early exit penalty cannot exceed {}", MAX_EARLY_EXIT_PENALTY)
+        })
     }
+    Ok(())
 }
 
-pub fn percent_vested_for(deps: Deps, env: Env, depositor: String) -> u64 {
-    let bond = bond_info(deps, depositor).unwrap();
-    let blocks_since_last = env.block.height - bond.last_block;
-    let vesting = bond.vesting;
-    return blocks_since_last * 10000 / vesting;
-    
+// Keeps RedeemMany within the block gas limit, a limit of zero turns it off
+pub fn set_redeem_batch_limit(mut deps: DepsMut, info: MessageInfo, limit: u32) -> Result<Response, ContractError>{
+    only_admin(deps.branch(), info)?;
+    assert_no_timelock(deps.as_ref())?;
+    REDEEM_BATCH_LIMIT.save(deps.storage, &limit)?;
+    Ok(Response::default())
 }
 
-pub fn pending_payout(deps: Deps, env: Env, depositor: String) -> StdResult<u64>{
-    let percent_vested = percent_vested_for(deps, env, depositor.clone());
-    let payout = bond_info(deps, depositor).unwrap().payout;
-    let pending_payout: u64;
-    if percent_vested >= 10000{
-        pending_payout = payout;
-    }else{
-        pending_payout = payout * percent_vested / 10000;
+pub fn initialize_bond_terms(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    terms_data: Terms,
+    initial_debt: u64
+) -> Result<Response, ContractError> {
+    only_admin(deps.branch(), info)?;
+    let existing = MARKETS.may_load(deps.storage, U64Key::new(DEFAULT_MARKET))?;
+    if let Some(market) = &existing {
+        assert_no_timelock(deps.as_ref())?;
+        // Re-running Init would wipe the debt of live bonds
+        if current_debt(market, env.clone()) != 0 {
+            return Err(ContractError::AlreadyInitialized{})
+        }
     }
-    Ok (pending_payout)
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{coins, from_binary};
+    validate_pricing(&terms_data.pricing)?;
+    validate_expiry(&env, terms_data.fixed_expiry)?;
+    validate_schedule(&terms_data)?;
+    validate_wrapped_payout(deps.as_ref(), &env, &terms_data)?;
 
-    fn init_msg() -> InstantiateMsg {
-        let addr_string = "terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8".to_string();
-        
-        // let addr = Addr::unchecked(addr_string);
-        return InstantiateMsg {
-            admin: addr_string.clone(),
-            treasury: addr_string.clone(),
-            dao: addr_string.clone(),
-            staking: addr_string.clone(),
-            total_debt: 0,
-            last_decay: 0,
-       }
+    let quote_denom = match &existing {
+        Some(market) => market.quote_denom.clone(),
+        None => DEFAULT_QUOTE.may_load(deps.storage)?.unwrap_or_else(|| DEFAULT_QUOTE_DENOM.to_string())
+    };
+    let market = Market{
+        quote_denom,
+        auction: open_auction(&terms_data.pricing),
+        terms: terms_data,
+        total_debt: initial_debt,
+        last_decay: env.block.height,
+        last_decay_time: env.block.time.seconds(),
+        adjust: existing.and_then(|market| market.adjust),
+        closed: false
+    };
+    MARKETS.save(deps.storage, U64Key::new(DEFAULT_MARKET), &market)?;
+    if MARKET_COUNT.may_load(deps.storage)?.unwrap_or(0) == DEFAULT_MARKET {
+        MARKET_COUNT.save(deps.storage, &(DEFAULT_MARKET + 1))?;
     }
 
-    #[test]
-    fn proper_initialization() {
-        let mut deps = mock_dependencies(&[]);
-        let msg = init_msg();
-       
-        let info = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(1000, "uusd"));
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
-        
-        assert_eq!(0, res.messages.len());
-    }
+    Ok(Response::default())
+}
 
-    #[test]
+// Wrapped markets stake payouts for the bond itself, shares kept in warmup could not be handed out
+fn validate_wrapped_payout(deps: Deps, env: &Env, terms: &Terms) -> Result<(), ContractError> {
+    if !terms.wrapped_payout {
+        return Ok(())
+    }
+    let state = STATE.load(deps.storage)?;
+    if state.wrapped_token.is_none() {
+        return Err(ContractError::InvalidTerms{ reason: "wrapped payouts need the wsPHS token".to_string() })
+    }
+    if !warmup_exempt(deps, env, &state)? {
+        return Err(ContractError::InvalidTerms{ reason: "bond must be exempt from the staking warmup".to_string() })
+    }
+    Ok(())
+}
+
+fn warmup_exempt(deps: Deps, env: &Env, state: &State) -> StdResult<bool> {
+    let res: WarmupExemptResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart{
+        contract_addr: state.staking.to_string(),
+        msg: to_binary(&StakingQueryMsg::WarmupExempt{ address: env.contract.address.to_string() })?
+    }))?;
+    Ok(res.exempt)
+}
+
+// Additional markets get the next free id, the first one created before Init takes the default id
+pub fn create_market(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    quote_denom: String,
+    terms: Terms,
+    initial_debt: u64
+) -> Result<Response, ContractError> {
+    only_admin(deps.branch(), info)?;
+    validate_pricing(&terms.pricing)?;
+    validate_expiry(&env, terms.fixed_expiry)?;
+    validate_schedule(&terms)?;
+    validate_wrapped_payout(deps.as_ref(), &env, &terms)?;
+
+    let market_id = MARKET_COUNT.may_load(deps.storage)?.unwrap_or(0);
+    let market = Market{
+        quote_denom,
+        auction: open_auction(&terms.pricing),
+        terms,
+        total_debt: initial_debt,
+        last_decay: env.block.height,
+        last_decay_time: env.block.time.seconds(),
+        adjust: None,
+        closed: false
+    };
+    MARKETS.save(deps.storage, U64Key::new(market_id), &market)?;
+    MARKET_COUNT.save(deps.storage, &(market_id + 1))?;
+
+    Ok(Response::new()
+        .add_attribute("method", "create_market")
+        .add_attribute("market_id", market_id.to_string()))
+}
+
+// Closed markets refuse deposits, existing bonds keep vesting and can be redeemed
+pub fn close_market(mut deps: DepsMut, info: MessageInfo, market_id: u64) -> Result<Response, ContractError> {
+    only_admin(deps.branch(), info)?;
+    let mut market = load_market(deps.storage, market_id)?;
+    market.closed = true;
+    MARKETS.save(deps.storage, U64Key::new(market_id), &market)?;
+    Ok(Response::new()
+        .add_attribute("method", "close_market")
+        .add_attribute("market_id", market_id.to_string()))
+}
+
+pub fn set_guardian(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    address: Option<String>
+) -> Result<Response, ContractError>{
+    only_admin(deps.branch(), info)?;
+    let guardian = match address {
+        Some(address) => Some(deps.api.addr_validate(&address)?),
+        None => None
+    };
+    BOND_GUARDIAN.set(deps.branch(), guardian)?;
+    Ok(Response::default())
+}
+
+// The guardian can pause on its own, lifting a pause is left to the admin
+pub fn pause(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    deposits: bool,
+    redemptions: bool
+) -> Result<Response, ContractError>{
+    only_admin_or_guardian(deps.branch(), info)?;
+    let mut pause_status = PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default();
+    pause_status.deposits = pause_status.deposits || deposits;
+    pause_status.redemptions = pause_status.redemptions || redemptions;
+    PAUSE_STATUS.save(deps.storage, &pause_status)?;
+    Ok(Response::new()
+        .add_attribute("method", "pause")
+        .add_attribute("deposits", pause_status.deposits.to_string())
+        .add_attribute("redemptions", pause_status.redemptions.to_string()))
+}
+
+pub fn unpause(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    deposits: bool,
+    redemptions: bool
+) -> Result<Response, ContractError>{
+    only_admin(deps.branch(), info)?;
+    let mut pause_status = PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default();
+    pause_status.deposits = pause_status.deposits && !deposits;
+    pause_status.redemptions = pause_status.redemptions && !redemptions;
+    PAUSE_STATUS.save(deps.storage, &pause_status)?;
+    Ok(Response::new()
+        .add_attribute("method", "unpause")
+        .add_attribute("deposits", pause_status.deposits.to_string())
+        .add_attribute("redemptions", pause_status.redemptions.to_string()))
+}
+
+pub fn allow_vested_redemptions(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    allowed: bool
+) -> Result<Response, ContractError>{
+    only_admin(deps.branch(), info)?;
+    let mut pause_status = PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default();
+    pause_status.allow_vested_redemptions = allowed;
+    PAUSE_STATUS.save(deps.storage, &pause_status)?;
+    Ok(Response::default())
+}
+
+// Admin handover is two-step: the current admin proposes, the new admin accepts before the expiry
+pub fn propose_new_admin(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    expiry: Option<Expiration>
+) -> Result<Response, ContractError>{
+    only_admin(deps.branch(), info)?;
+    let pending_admin = PendingAdmin{
+        address: deps.api.addr_validate(&address)?,
+        expiry
+    };
+    PENDING_ADMIN.save(deps.storage, &pending_admin)?;
+    Ok(Response::new()
+        .add_attribute("method", "propose_new_admin")
+        .add_attribute("pending_admin", address))
+}
+
+pub fn accept_admin(mut deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError>{
+    let pending_admin = match PENDING_ADMIN.may_load(deps.storage)? {
+        Some(pending_admin) => pending_admin,
+        None => return Err(ContractError::NoPendingAdmin{})
+    };
+    if pending_admin.address != info.sender {
+        return Err(ContractError::Unauthorized{})
+    }
+    if let Some(expiry) = pending_admin.expiry {
+        if expiry.is_expired(&env.block) {
+            return Err(ContractError::AdminProposalExpired{})
+        }
+    }
+    BOND_ADMIN.set(deps.branch(), Some(pending_admin.address))?;
+    PENDING_ADMIN.remove(deps.storage);
+    Ok(Response::new()
+        .add_attribute("method", "accept_admin")
+        .add_attribute("admin", info.sender))
+}
+
+pub fn renounce_admin(mut deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError>{
+    only_admin(deps.branch(), info)?;
+    BOND_ADMIN.set(deps.branch(), None)?;
+    PENDING_ADMIN.remove(deps.storage);
+    Ok(Response::new().add_attribute("method", "renounce_admin"))
+}
+
+fn only_admin_or_guardian(deps: DepsMut, info: MessageInfo) -> Result< (),ContractError>{
+    let is_guardian = BOND_GUARDIAN.is_admin(deps.as_ref(), &info.sender)?;
+    if is_guardian {
+        return Ok(())
+    }
+    only_admin(deps, info)
+}
+
+fn only_admin(deps: DepsMut, info: MessageInfo) -> Result< (),ContractError>{
+    let res = BOND_ADMIN.assert_admin(deps.as_ref(), &info.sender);
+    match res{
+        Ok(()) => return Ok(()),
+        Err(_not_admin) => return Err(ContractError::Unauthorized{})
+    }; 
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    let height = env.block.height;
+    let time = env.block.time.seconds();
+    // Block counts become durations, past heights become the time they were roughly reached at
+    let to_seconds = |blocks: u64| blocks * msg.seconds_per_block;
+    let to_time = |block: u64| match block > height {
+        true => time + to_seconds(block - height),
+        false => time.saturating_sub(to_seconds(height - block))
+    };
+
+    migrate_single_market(deps.storage, &to_time)?;
+    // The single market contract had no guardian, pausing and the pause query need the item to exist
+    if BOND_GUARDIAN.get(deps.as_ref()).is_err() {
+        BOND_GUARDIAN.set(deps.branch(), None)?;
+    }
+
+    for market_id in msg.seconds_markets.iter() {
+        let mut market = load_market(deps.storage, *market_id)?;
+        if market.terms.vesting_unit == VestingUnit::Seconds {
+            continue;
+        }
+        market.terms.vesting_unit = VestingUnit::Seconds;
+        market.terms.vesting_term = to_seconds(market.terms.vesting_term);
+        market.terms.cliff = to_seconds(market.terms.cliff);
+        if let VestingCurve::Step{ interval } = market.terms.curve {
+            market.terms.curve = VestingCurve::Step{ interval: to_seconds(interval) };
+        }
+        market.last_decay_time = to_time(market.last_decay);
+        if let Some(adjust) = market.adjust.as_mut() {
+            adjust.buffer = to_seconds(adjust.buffer);
+            adjust.last_time = to_time(adjust.last_block);
+        }
+        MARKETS.save(deps.storage, U64Key::new(*market_id), &market)?;
+    }
+
+    let positions = bonds()
+        .range(deps.storage, None, None, Order::Ascending)
+        .filter(|item| match item {
+            Ok((_, bond)) => msg.seconds_markets.contains(&bond.market_id) &&
+                bond.vesting_unit == VestingUnit::Blocks && bond.expiry.is_none(),
+            Err(_) => true
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    for (key, bond) in positions {
+        let mut converted = bond.clone();
+        converted.vesting_unit = VestingUnit::Seconds;
+        converted.vesting = to_seconds(bond.vesting);
+        converted.last_time = to_time(bond.last_block);
+        converted.cliff_end = to_time(bond.cliff_end);
+        if let VestingCurve::Step{ interval } = bond.curve {
+            converted.curve = VestingCurve::Step{ interval: to_seconds(interval) };
+        }
+        // The position changes clock, so replace moves its maturity index entry along
+        bonds().replace(deps.storage, U64Key::from(key), Some(&converted), Some(&bond))?;
+    }
+
+    Ok(Response::new().add_attribute("method", "migrate"))
+}
+
+// Contracts from before markets keep their terms, debt and adjustment in single items and
+// positions by owner, they become market 0 and positions of it
+fn migrate_single_market(storage: &mut dyn Storage, to_time: &dyn Fn(u64) -> u64) -> Result<(), ContractError> {
+    let terms = match LEGACY_TERMS.may_load(storage)? {
+        Some(terms) => terms,
+        None => return Ok(())
+    };
+    let debt = LEGACY_DEBT.load(storage)?;
+    let market = Market{
+        quote_denom: DEFAULT_QUOTE_DENOM.to_string(),
+        auction: open_auction(&terms.pricing),
+        terms,
+        total_debt: debt.total_debt,
+        last_decay: debt.last_decay,
+        last_decay_time: to_time(debt.last_decay),
+        adjust: LEGACY_ADJUST.may_load(storage)?.map(|adjust| Adjust{ last_time: to_time(adjust.last_block), ..adjust }),
+        closed: false
+    };
+    MARKETS.save(storage, U64Key::new(DEFAULT_MARKET), &market)?;
+    MARKET_COUNT.save(storage, &(DEFAULT_MARKET + 1))?;
+    DEFAULT_QUOTE.save(storage, &DEFAULT_QUOTE_DENOM.to_string())?;
+    FEES_COLLECTED.save(storage, &FeesCollected::default())?;
+    LEGACY_TERMS.remove(storage);
+    LEGACY_ADJUST.remove(storage);
+
+    let positions = LEGACY_BOND
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut bond_id = BOND_COUNT.may_load(storage)?.unwrap_or(0);
+    for (owner, legacy) in positions {
+        let owner = Addr::unchecked(String::from_utf8(owner).map_err(StdError::invalid_utf8)?);
+        LEGACY_BOND.remove(storage, &owner);
+        let bond = Bond{
+            owner,
+            market_id: DEFAULT_MARKET,
+            payout: legacy.payout,
+            claimable: 0,
+            vesting: legacy.vesting,
+            last_block: legacy.last_block,
+            price_paid: legacy.price_paid,
+            last_time: to_time(legacy.last_block),
+            expiry: None,
+            vesting_unit: VestingUnit::Blocks,
+            cliff_end: 0,
+            curve: VestingCurve::Linear,
+            wrapped: false
+        };
+        bonds().save(storage, U64Key::new(bond_id), &bond)?;
+        bond_id += 1;
+    }
+    BOND_COUNT.save(storage, &bond_id)?;
+    Ok(())
+}
+
+fn bond_id(key: &[u8]) -> u64 {
+    let mut id = [0u8; 8];
+    id.copy_from_slice(key);
+    u64::from_be_bytes(id)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::BondInfo{address, market_id} => to_binary(&query_bond_info(deps, address, market_id)?),
+        QueryMsg::BondPriceInUsd{market_id} => to_binary(&query_bond_price_in_usd(deps, _env, market_id)?),
+        QueryMsg::MaxPayout{address, max_pay} => to_binary(&query_max_payout(deps, address, max_pay)?),
+        QueryMsg::PendingPayout{address, market_id} => to_binary(&query_pending_payout(deps, _env, address, market_id)?),
+        QueryMsg::Config{} => to_binary(&query_config(deps)?),
+        QueryMsg::Terms{market_id} => to_binary(&query_terms(deps, market_id)?),
+        QueryMsg::State{market_id} => to_binary(&query_state(deps, market_id)?),
+        QueryMsg::CurrentDebt{market_id} => to_binary(&query_current_debt(deps, _env, market_id)?),
+        QueryMsg::DebtRatio{market_id} => to_binary(&query_debt_ratio(deps, _env, market_id)?),
+        QueryMsg::StandardizedDebtRatio{market_id} => to_binary(&query_standardized_debt_ratio(deps, _env, market_id)?),
+        QueryMsg::BondPrice{market_id} => to_binary(&query_bond_price(deps, _env, market_id)?),
+        QueryMsg::PercentVestedFor{address, market_id} => to_binary(&query_percent_vested_for(deps, _env, address, market_id)?),
+        QueryMsg::FeesCollected{} => to_binary(&query_fees_collected(deps)?),
+        QueryMsg::PauseStatus{} => to_binary(&query_pause_status(deps)?),
+        QueryMsg::EarlyExit{bond_id} => to_binary(&query_early_exit(deps, _env, bond_id)?),
+        QueryMsg::MaturedBonds{start_after, limit} => to_binary(&query_matured_bonds(deps, _env, start_after, limit)?),
+        QueryMsg::AllBonds{start_after, limit} => to_binary(&query_all_bonds(deps, _env, start_after, limit)?),
+        QueryMsg::Admin{} => to_binary(&query_admin(deps)?),
+        QueryMsg::PendingChanges{} => to_binary(&query_pending_changes(deps)?),
+        QueryMsg::MarketPrice{market_id} => to_binary(&query_market_price(deps, _env, market_id)?),
+        QueryMsg::CurrentCapacity{market_id} => to_binary(&query_current_capacity(deps, _env, market_id)?),
+        QueryMsg::MarketInfo{market_id} => to_binary(&query_market_info(deps, _env, market_id)?),
+        QueryMsg::Markets{start_after, limit} => to_binary(&query_markets(deps, start_after, limit)?)
+    }
+}
+
+fn query_bond_info(deps: Deps, address: String, market_id: u64) -> StdResult<BondInfoResponse> {
+    let owner = deps.api.addr_validate(&address)?;
+    let (key, bond) = match bonds().idx.owner.item(deps.storage, (owner, U64Key::new(market_id)))? {
+        Some(item) => item,
+        None => return Ok(BondInfoResponse::default())
+    };
+    Ok(BondInfoResponse{
+        bond_id: Some(bond_id(&key)),
+        payout: bond.payout,
+        claimable: bond.claimable,
+        vesting: bond.vesting,
+        last_block: bond.last_block,
+        price_paid: bond.price_paid,
+        last_time: bond.last_time,
+        expiry: bond.expiry,
+        vesting_unit: bond.vesting_unit,
+        cliff_end: bond.cliff_end,
+        curve: bond.curve,
+        wrapped: bond.wrapped
+    })
+}
+
+fn query_bond_price_in_usd(deps: Deps, env: Env, market_id: u64) -> StdResult<BondPriceInUsdResponse> {
+    let market = MARKETS.load(deps.storage, U64Key::new(market_id))?;
+    Ok(BondPriceInUsdResponse{ price_in_usd: bond_price_in_usd(deps, env, &market)? })
+}
+
+fn query_max_payout(deps: Deps, address: String, max_pay: u64) -> StdResult<MaxPayoutResponse> {
+    Ok(MaxPayoutResponse{ max_payout: max_payout(deps, address, max_pay)? })
+}
+
+fn query_pending_payout(deps: Deps, env: Env, address: String, market_id: u64) -> StdResult<PendingPayoutResponse> {
+    Ok(PendingPayoutResponse{ pending_payout: pending_payout(deps, env, address, market_id)? })
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let state = STATE.load(deps.storage)?;
+    Ok(ConfigResponse{
+        treasury: state.treasury.to_string(),
+        dao: state.dao.to_string(),
+        staking: state.staking.to_string(),
+        fee_destination: state.fee_destination,
+        timelock: state.timelock,
+        wrapped_token: state.wrapped_token.map(String::from)
+    })
+}
+
+fn query_terms(deps: Deps, market_id: u64) -> StdResult<TermsResponse> {
+    let terms = MARKETS.load(deps.storage, U64Key::new(market_id))?.terms;
+    Ok(TermsResponse{
+        control_variable: terms.control_variable,
+        vesting_term: terms.vesting_term,
+        minimum_price: terms.minimum_price,
+        max_payout: terms.max_payout,
+        fee: terms.fee,
+        max_debt: terms.max_debt,
+        fixed_expiry: terms.fixed_expiry,
+        vesting_unit: terms.vesting_unit,
+        cliff: terms.cliff,
+        curve: terms.curve,
+        wrapped_payout: terms.wrapped_payout,
+        pricing: terms.pricing
+    })
+}
+
+fn query_state(deps: Deps, market_id: u64) -> StdResult<StateResponse> {
+    let market = MARKETS.load(deps.storage, U64Key::new(market_id))?;
+    Ok(StateResponse{
+        total_debt: market.total_debt,
+        last_decay: market.last_decay,
+        last_decay_time: market.last_decay_time
+    })
+}
+
+fn query_current_debt(deps: Deps, env: Env, market_id: u64) -> StdResult<CurrentDebtResponse> {
+    let market = MARKETS.load(deps.storage, U64Key::new(market_id))?;
+    Ok(CurrentDebtResponse{ current_debt: current_debt(&market, env) })
+}
+
+fn query_debt_ratio(deps: Deps, env: Env, market_id: u64) -> StdResult<DebtRatioResponse> {
+    let state = STATE.load(deps.storage)?;
+    let market = MARKETS.load(deps.storage, U64Key::new(market_id))?;
+    Ok(DebtRatioResponse{ debt_ratio: debt_ratio(deps, env, &market, String::from(state.treasury))? })
+}
+
+// Reserve bonds are priced in UST, so the standardized ratio is the plain debt ratio.
+// Liquidity bonds would scale this by the LP price feed.
+fn query_standardized_debt_ratio(deps: Deps, env: Env, market_id: u64) -> StdResult<StandardizedDebtRatioResponse> {
+    let state = STATE.load(deps.storage)?;
+    let market = MARKETS.load(deps.storage, U64Key::new(market_id))?;
+    Ok(StandardizedDebtRatioResponse{ debt_ratio: debt_ratio(deps, env, &market, String::from(state.treasury))? })
+}
+
+fn query_bond_price(deps: Deps, env: Env, market_id: u64) -> StdResult<BondPriceResponse> {
+    let market = MARKETS.load(deps.storage, U64Key::new(market_id))?;
+    Ok(BondPriceResponse{ price: bond_price(deps, env, &market)? })
+}
+
+fn query_fees_collected(deps: Deps) -> StdResult<FeesCollectedResponse> {
+    let fees = FEES_COLLECTED.may_load(deps.storage)?.unwrap_or_default();
+    Ok(FeesCollectedResponse{
+        total: fees.dao + fees.burned + fees.staking,
+        dao: fees.dao,
+        burned: fees.burned,
+        staking: fees.staking
+    })
+}
+
+fn query_market_price(deps: Deps, env: Env, market_id: u64) -> StdResult<MarketPriceResponse> {
+    let market = MARKETS.load(deps.storage, U64Key::new(market_id))?;
+    Ok(MarketPriceResponse{ price: bond_price(deps, env, &market)? })
+}
+
+// Auctions report the payout tokens left, other markets the debt headroom
+fn query_current_capacity(deps: Deps, env: Env, market_id: u64) -> StdResult<CurrentCapacityResponse> {
+    let market = MARKETS.load(deps.storage, U64Key::new(market_id))?;
+    let capacity = match &market.auction {
+        Some(auction) => auction.capacity,
+        None => market.terms.max_debt.saturating_sub(current_debt(&market, env))
+    };
+    Ok(CurrentCapacityResponse{ capacity })
+}
+
+fn query_market_info(deps: Deps, env: Env, market_id: u64) -> StdResult<MarketInfoResponse> {
+    let market = MARKETS.load(deps.storage, U64Key::new(market_id))?;
+    let (auction_terms, auction) = match (&market.terms.pricing, &market.auction) {
+        (PricingModel::SequentialDutchAuction(auction_terms), Some(auction)) => (auction_terms, auction),
+        _ => return Err(StdError::generic_err("market is not a sequential dutch auction"))
+    };
+    let time = env.block.time.seconds();
+    Ok(MarketInfoResponse{
+        active: !market.closed && auction_terms.is_active(auction, time),
+        start: auction_terms.start,
+        conclusion: auction_terms.conclusion,
+        capacity: auction.capacity,
+        sold: auction.sold,
+        purchased: auction.purchased,
+        max_payout: auction_terms.max_payout(),
+        target_sell_rate: auction_terms.target_sell_rate(),
+        control_variable: auction.control_variable,
+        current_debt: auction.current_debt(auction_terms, time),
+        price: bond_price(deps, env, &market)?
+    })
+}
+
+fn query_markets(deps: Deps, start_after: Option<u64>, limit: Option<u32>) -> StdResult<MarketsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+    let markets = MARKETS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (key, market) = item?;
+            let mut id = [0u8; 8];
+            id.copy_from_slice(&key);
+            Ok(MarketResponse{
+                id: u64::from_be_bytes(id),
+                quote_denom: market.quote_denom,
+                terms: market.terms,
+                total_debt: market.total_debt,
+                closed: market.closed
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(MarketsResponse{ markets })
+}
+
+fn query_pending_changes(deps: Deps) -> StdResult<PendingChangesResponse> {
+    let changes = PENDING_CHANGES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (key, pending) = item?;
+            let mut id = [0u8; 8];
+            id.copy_from_slice(&key);
+            Ok(PendingChangeResponse{
+                id: u64::from_be_bytes(id),
+                change: pending.change,
+                executable_at: pending.executable_at
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(PendingChangesResponse{ changes })
+}
+
+fn query_admin(deps: Deps) -> StdResult<AdminResponse> {
+    let admin = BOND_ADMIN.get(deps)?;
+    let pending_admin = PENDING_ADMIN.may_load(deps.storage)?;
+    Ok(AdminResponse{
+        admin: admin.map(String::from),
+        pending_admin: pending_admin.as_ref().map(|pending| pending.address.to_string()),
+        pending_expiry: pending_admin.and_then(|pending| pending.expiry)
+    })
+}
+
+fn query_pause_status(deps: Deps) -> StdResult<PauseStatusResponse> {
+    let pause_status = PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default();
+    let guardian = BOND_GUARDIAN.get(deps)?;
+    Ok(PauseStatusResponse{
+        guardian: guardian.map(String::from),
+        deposits_paused: pause_status.deposits,
+        redemptions_paused: pause_status.redemptions,
+        allow_vested_redemptions: pause_status.allow_vested_redemptions
+    })
+}
+
+fn query_early_exit(deps: Deps, env: Env, bond_id: u64) -> StdResult<EarlyExitResponse> {
+    let terms = EARLY_EXIT.may_load(deps.storage)?
+        .ok_or_else(|| StdError::generic_err(ContractError::EarlyExitDisabled{}.to_string()))?;
+    let bond = bonds().load(deps.storage, U64Key::new(bond_id))?;
+    Ok(early_exit_amounts(&bond, &env, &terms))
+}
+
+fn query_matured_bonds(deps: Deps, env: Env, start_after: Option<MaturedCursor>, limit: Option<u32>) -> StdResult<MaturedBondsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let mut matured_bonds = vec![];
+    let mut next_start_after = None;
+    for (clock, now) in [(MATURES_AT_HEIGHT, env.block.height), (MATURES_AT_TIME, env.block.time.seconds())] {
+        // The cursor bounds the index key itself, the bond it names may be gone by now
+        let min = match &start_after {
+            Some(cursor) if cursor.clock > clock => continue,
+            Some(cursor) if cursor.clock == clock =>
+                Some(Bound::exclusive((U64Key::new(cursor.maturity), U64Key::new(cursor.bond_id)).joined_key())),
+            _ => None
+        };
+        let max = Some(Bound::inclusive((U64Key::new(now), U64Key::new(u64::MAX)).joined_key()));
+        let matured = bonds().idx.maturity
+            .sub_prefix(U8Key::new(clock))
+            .range(deps.storage, min, max, Order::Ascending)
+            .take(limit - matured_bonds.len())
+            .collect::<StdResult<Vec<_>>>()?;
+        for (key, bond) in matured {
+            let (clock, maturity) = bond.maturity();
+            next_start_after = Some(MaturedCursor{ clock, maturity, bond_id: bond_id(&key) });
+            matured_bonds.push(MaturedBondResponse{
+                bond_id: bond_id(&key),
+                owner: bond.owner.to_string(),
+                market_id: bond.market_id,
+                payout: bond.claimable + bond.payout
+            });
+        }
+    }
+    Ok(MaturedBondsResponse{ bonds: matured_bonds, next_start_after })
+}
+
+fn query_all_bonds(deps: Deps, env: Env, start_after: Option<u64>, limit: Option<u32>) -> StdResult<AllBondsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+    let positions = bonds()
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (key, bond) = item?;
+            Ok(BondResponse{
+                bond_id: bond_id(&key),
+                owner: bond.owner.to_string(),
+                market_id: bond.market_id,
+                payout: bond.payout,
+                claimable: bond.claimable,
+                vesting_left: vesting_left(&bond, &env),
+                price_paid: bond.price_paid,
+                pending_payout: pending(&bond, &env),
+                wrapped: bond.wrapped
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(AllBondsResponse{ bonds: positions })
+}
+
+fn query_percent_vested_for(deps: Deps, env: Env, address: String, market_id: u64) -> StdResult<PercentVestedForResponse> {
+    Ok(PercentVestedForResponse{ percent_vested: percent_vested_for(deps, env, address, market_id)? })
+}
+
+fn bond_info(deps: Deps, depositor: String, market_id: u64) -> StdResult<Option<Bond>> {
+    let owner = deps.api.addr_validate(&depositor)?;
+    let bond = bonds().idx.owner.item(deps.storage, (owner, U64Key::new(market_id)))?;
+    Ok(bond.map(|(_, bond)| bond))
+}
+
+pub fn percent_vested_for(deps: Deps, env: Env, depositor: String, market_id: u64) -> StdResult<u64> {
+    match bond_info(deps, depositor, market_id)? {
+        Some(bond) => Ok(percent_vested(&bond, &env)),
+        None => Ok(0)
+    }
+}
+
+fn percent_vested(bond: &Bond, env: &Env) -> u64 {
+    let (vested, vesting) = vesting_progress(bond, env);
+    if vested >= vesting {
+        return 10000;
+    }
+    vested * 10000 / vesting
+}
+
+// Vested and total length of a position's remaining period, on the clock it vests with
+fn vesting_progress(bond: &Bond, env: &Env) -> (u64, u64) {
+    let (now, since, vesting) = match (bond.expiry, &bond.vesting_unit) {
+        (Some(expiry), _) => (env.block.time.seconds(), bond.last_time, expiry - bond.last_time),
+        (None, VestingUnit::Blocks) => (env.block.height, bond.last_block, bond.vesting),
+        (None, VestingUnit::Seconds) => (env.block.time.seconds(), bond.last_time, bond.vesting)
+    };
+    let elapsed = now - since;
+    if elapsed >= vesting {
+        return (vesting, vesting);
+    }
+    if now < bond.cliff_end {
+        return (0, vesting);
+    }
+    let vested = match bond.curve {
+        VestingCurve::Linear => elapsed,
+        VestingCurve::Step{ interval } => elapsed - elapsed % interval
+    };
+    (vested, vesting)
+}
+
+// Time to maturity on the position's own clock, independent of how its payout vests
+fn vesting_left(bond: &Bond, env: &Env) -> u64 {
+    let (clock, maturity) = bond.maturity();
+    let now = match clock {
+        MATURES_AT_HEIGHT => env.block.height,
+        _ => env.block.time.seconds()
+    };
+    maturity.saturating_sub(now)
+}
+
+pub fn pending_payout(deps: Deps, env: Env, depositor: String, market_id: u64) -> StdResult<u64>{
+    match bond_info(deps, depositor, market_id)? {
+        Some(bond) => Ok(pending(&bond, &env)),
+        None => Ok(0)
+    }
+}
+
+// What a redemption of the position would pay out now
+fn pending(bond: &Bond, env: &Env) -> u64 {
+    let percent_vested = percent_vested(bond, env);
+    let payout = bond.payout;
+    let pending_payout: u64;
+    if percent_vested >= 10000{
+        pending_payout = payout;
+    }else{
+        pending_payout = payout * percent_vested / 10000;
+    }
+    bond.claimable + pending_payout
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pricing::{FixedPrice, SequentialDutchAuction};
+    use crate::state::LegacyBond;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+    use cosmwasm_std::{coins, from_binary, from_slice, Empty, OwnedDeps, Querier, QuerierResult, SystemResult, ContractResult};
+
+    // Answers SharesFor as if the staking index had doubled, and WarmupExempt with `exempt`
+    struct StakingQuerier {
+        base: MockQuerier,
+        exempt: bool
+    }
+
+    impl Querier for StakingQuerier {
+        fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+            match from_slice::<QueryRequest<Empty>>(bin_request) {
+                Ok(QueryRequest::Wasm(WasmQuery::Smart{ msg, .. })) => {
+                    let res = match from_binary(&msg) {
+                        Ok(StakingQueryMsg::SharesFor{ amount }) =>
+                            to_binary(&SharesForResponse{ shares: amount.multiply_ratio(1u128, 2u128) }),
+                        Ok(StakingQueryMsg::WarmupExempt{ .. }) =>
+                            to_binary(&WarmupExemptResponse{ exempt: self.exempt }),
+                        _ => panic!("unexpected staking query")
+                    };
+                    SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+                },
+                _ => self.base.raw_query(bin_request)
+            }
+        }
+    }
+
+    fn staking_dependencies(exempt: bool) -> OwnedDeps<MockStorage, MockApi, StakingQuerier> {
+        OwnedDeps{
+            storage: MockStorage::default(),
+            api: MockApi::default(),
+            querier: StakingQuerier{ base: MockQuerier::<Empty>::new(&[]), exempt }
+        }
+    }
+
+    fn init_msg() -> InstantiateMsg {
+        let addr_string = "terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8".to_string();
+        
+        // let addr = Addr::unchecked(addr_string);
+        return InstantiateMsg {
+            admin: addr_string.clone(),
+            treasury: addr_string.clone(),
+            dao: addr_string.clone(),
+            staking: addr_string.clone(),
+            fee_destination: None,
+            guardian: Some("terra1e8ryd9ezefuucd4mje33zdms9m2s90m57878v9".to_string()),
+            timelock: None,
+            quote_denom: None,
+            wrapped_token: None,
+            total_debt: 0,
+            last_decay: 0,
+       }
+    }
+
+    fn default_market(deps: Deps) -> Market {
+        MARKETS.load(deps.storage, U64Key::new(0)).unwrap()
+    }
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = init_msg();
+       
+        let info = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(1000, "uusd"));
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+        
+        assert_eq!(0, res.messages.len());
+
+        // Init keeps the quote asset the bond was instantiated for
+        let mut deps = mock_dependencies(&[]);
+        let msg = InstantiateMsg{ quote_denom: Some("uluna".to_string()), ..init_msg() };
+        let info = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Init {
+            control_variable: 300000 ,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 0,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+        }).unwrap();
+        assert_eq!("uluna", default_market(deps.as_ref()).quote_denom);
+    }
+
+    #[test]
     fn minimum_price() {
         let mut deps = mock_dependencies(&coins(2, "token"));
 
@@ -527,7 +1944,13 @@ mod tests {
             max_payout: 50,
             fee: 1000,
             max_debt: 1000000000000000,
-            initial_debt: 0
+            initial_debt: 0,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
 
         };
 
@@ -535,9 +1958,9 @@ mod tests {
         let _res = execute(deps.as_mut(), mock_env(), info.clone(), terms_msg);
 
         //While initial debt is 0, price should be minimum price
-        assert_eq!(minimum_price, bond_price(deps.as_ref(), mock_env()).unwrap());
+        assert_eq!(minimum_price, bond_price(deps.as_ref(), mock_env(), &default_market(deps.as_ref())).unwrap());
 
-        assert_eq!(minimum_price * 10000, bond_price_in_usd(deps.as_ref(), mock_env()).unwrap());
+        assert_eq!(minimum_price * 10000, bond_price_in_usd(deps.as_ref(), mock_env(), &default_market(deps.as_ref())).unwrap());
         
     }
 
@@ -559,7 +1982,13 @@ mod tests {
             max_payout: 50,
             fee: 1000,
             max_debt: 1000000000000000,
-            initial_debt
+            initial_debt,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
 
         };
 
@@ -567,12 +1996,13 @@ mod tests {
         let _res = execute(deps.as_mut(), mock_env(), info.clone(), terms_msg);
         
         // As block height is staying same in testing env, initial debt should not decay
-        assert_eq!(initial_debt, current_debt(deps.as_ref(), mock_env()).unwrap());
+        let market = default_market(deps.as_ref());
+        assert_eq!(initial_debt, current_debt(&market, mock_env()));
        
         //Further check debt ratio and price accroding to formula
-        assert_eq!(1666666, debt_ratio(deps.as_ref(), mock_env(), "terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8".to_string()).unwrap());
+        assert_eq!(1666666, debt_ratio(deps.as_ref(), mock_env(), &market, "terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8".to_string()).unwrap());
         // assert_eq!()
-        assert_eq!(50099, bond_price(deps.as_ref(), mock_env()).unwrap());
+        assert_eq!(50099, bond_price(deps.as_ref(), mock_env(), &market).unwrap());
         
         
     }
@@ -595,7 +2025,13 @@ mod tests {
             max_payout: 50,
             fee: 1000,
             max_debt: 1000000000000000,
-            initial_debt
+            initial_debt,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
 
         };
 
@@ -603,6 +2039,7 @@ mod tests {
         let _res = execute(deps.as_mut(), mock_env(), info.clone(), terms_msg);
         
         let deposit_msg = ExecuteMsg::Deposit{
+            market_id: 0,
             max_price: 50
         };
 
@@ -633,7 +2070,13 @@ mod tests {
             max_payout: 50,
             fee: 1000,
             max_debt: 1000000000000000,
-            initial_debt
+            initial_debt,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
 
         };
 
@@ -641,6 +2084,7 @@ mod tests {
         let _res = execute(deps.as_mut(), mock_env(), info.clone(), terms_msg);
         
         let deposit_msg = ExecuteMsg::Deposit{
+            market_id: 0,
             max_price: 500000
         };
 
@@ -672,7 +2116,13 @@ mod tests {
             max_payout: 50,
             fee: 1000,
             max_debt: 1000000000000000,
-            initial_debt
+            initial_debt,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
 
         };
 
@@ -680,6 +2130,7 @@ mod tests {
         let _res = execute(deps.as_mut(), mock_env(), info.clone(), terms_msg);
         
         let deposit_msg = ExecuteMsg::Deposit{
+            market_id: 0,
             max_price: 500000
         };
 
@@ -694,11 +2145,11 @@ mod tests {
     }
 
     #[test]
-    fn payout() {
+    fn monitoring_queries() {
         let mut deps = mock_dependencies(&coins(2, "token"));
 
         let msg = init_msg();
-        let info = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(20000000, "uusd"));
+        let info = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(2000000, "uusd"));
         let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg);
 
         let minimum_price = 10000;
@@ -711,22 +2162,1774 @@ mod tests {
             max_payout: 50,
             fee: 1000,
             max_debt: 1000000000000000,
-            initial_debt
+            initial_debt,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
 
         };
 
-        
         let _res = execute(deps.as_mut(), mock_env(), info.clone(), terms_msg);
-        
-        let deposit_msg = ExecuteMsg::Deposit{
-            max_price: 500000
-        };
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Config{}).unwrap();
+        let config: ConfigResponse = from_binary(&res).unwrap();
+        assert_eq!("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", config.treasury);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Terms{ market_id: 0 }).unwrap();
+        let terms: TermsResponse = from_binary(&res).unwrap();
+        assert_eq!(300000, terms.control_variable);
+        assert_eq!(28800, terms.vesting_term);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::State{ market_id: 0 }).unwrap();
+        let state: StateResponse = from_binary(&res).unwrap();
+        assert_eq!(initial_debt, state.total_debt);
+        assert_eq!(mock_env().block.height, state.last_decay);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::CurrentDebt{ market_id: 0 }).unwrap();
+        let current: CurrentDebtResponse = from_binary(&res).unwrap();
+        assert_eq!(initial_debt, current.current_debt);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::DebtRatio{ market_id: 0 }).unwrap();
+        let ratio: DebtRatioResponse = from_binary(&res).unwrap();
+        assert_eq!(1666666, ratio.debt_ratio);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::StandardizedDebtRatio{ market_id: 0 }).unwrap();
+        let standardized: StandardizedDebtRatioResponse = from_binary(&res).unwrap();
+        assert_eq!(ratio.debt_ratio, standardized.debt_ratio);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::BondPrice{ market_id: 0 }).unwrap();
+        let price: BondPriceResponse = from_binary(&res).unwrap();
+        assert_eq!(50099, price.price);
+    }
+
+    #[test]
+    fn payout() {
+        let mut deps = mock_dependencies(&coins(2, "token"));
+
+        let msg = init_msg();
+        let info = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(20000000, "uusd"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg);
+
+        let minimum_price = 10000;
+        let initial_debt = 100000000;
+
+        let terms_msg = ExecuteMsg::Init {
+            control_variable: 300000 ,
+            vesting_term: 28800,
+            minimum_price,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+
+        };
+
+        
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), terms_msg);
+        
+        let deposit_msg = ExecuteMsg::Deposit{
+            market_id: 0,
+            max_price: 500000
+        };
 
         let _res2 = execute(deps.as_mut(), mock_env(), info.clone(), deposit_msg); 
         
-        let res3 = query(deps.as_ref(), mock_env(), QueryMsg::BondInfo{address:"terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8".to_string() }).unwrap();
+        let res3 = query(deps.as_ref(), mock_env(), QueryMsg::BondInfo{address:"terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8".to_string(), market_id: 0 }).unwrap();
         
-        let value: Bond = from_binary(&res3).unwrap();
+        let value: BondInfoResponse = from_binary(&res3).unwrap();
         assert_eq!(39920900, value.payout);
     }
+
+    #[test]
+    fn fee_routing() {
+        let mut deps = mock_dependencies(&coins(2, "token"));
+
+        let msg = init_msg();
+        let info = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(20000000, "uusd"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg);
+
+        let terms_msg = ExecuteMsg::Init {
+            control_variable: 300000 ,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 100000000,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), terms_msg.clone());
+
+        let deposit_msg = ExecuteMsg::Deposit{
+            market_id: 0,
+            max_price: 500000
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), deposit_msg.clone()).unwrap();
+
+        // Treasury deposit followed by the fee transfer to the DAO
+        assert_eq!(2, res.messages.len());
+        assert_eq!(res.messages[1].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+            funds: vec![],
+            contract_addr: "terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8".to_string(),
+            msg: to_binary(&TreasuryExecuteMsg::Transfer{
+                recipient: "terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8".to_string(),
+                amount: Uint128::from(399209u64)
+            }).unwrap()
+        }));
+        assert_eq!("bond_fee", res.events[0].ty);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::FeesCollected{}).unwrap();
+        let fees: FeesCollectedResponse = from_binary(&res).unwrap();
+        assert_eq!(399209, fees.dao);
+
+        // Only the admin can reroute fees
+        let other = mock_info("terra1e8ryd9ezefuucd4mje33zdms9m2s90m57878v9", &[]);
+        let res = execute(deps.as_mut(), mock_env(), other, ExecuteMsg::SetFeeDestination{ destination: FeeDestination::Burn });
+        match res {
+            Err(ContractError::Unauthorized{}) => {},
+            _ => panic!("Must return unauthorized error")
+        }
+
+        let mut deps = mock_dependencies(&coins(2, "token"));
+        let mut msg = init_msg();
+        msg.fee_destination = Some(FeeDestination::Burn);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg);
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), terms_msg.clone());
+
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), deposit_msg.clone()).unwrap();
+        assert_eq!(res.messages[1].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+            funds: vec![],
+            contract_addr: "terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8".to_string(),
+            msg: to_binary(&TreasuryExecuteMsg::Burn{
+                amount: Uint128::from(399209u64)
+            }).unwrap()
+        }));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::FeesCollected{}).unwrap();
+        let fees: FeesCollectedResponse = from_binary(&res).unwrap();
+        assert_eq!(0, fees.dao);
+        assert_eq!(399209, fees.burned);
+        assert_eq!(fees.dao + fees.burned, fees.total);
+
+        // Staking fees are added to the rewards through an allowance, not sent to the contract
+        let mut deps = mock_dependencies(&coins(2, "token"));
+        let mut msg = init_msg();
+        msg.staking = "terra1e8ryd9ezefuucd4mje33zdms9m2s90m57878v9".to_string();
+        msg.fee_destination = Some(FeeDestination::Staking);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg);
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), terms_msg);
+
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), deposit_msg).unwrap();
+        assert_eq!(3, res.messages.len());
+        assert_eq!(res.messages[1].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+            funds: vec![],
+            contract_addr: "terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8".to_string(),
+            msg: to_binary(&TreasuryExecuteMsg::IncreaseAllowance{
+                spender: "terra1e8ryd9ezefuucd4mje33zdms9m2s90m57878v9".to_string(),
+                amount: Uint128::from(399209u64)
+            }).unwrap()
+        }));
+        assert_eq!(res.messages[2].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+            funds: vec![],
+            contract_addr: "terra1e8ryd9ezefuucd4mje33zdms9m2s90m57878v9".to_string(),
+            msg: to_binary(&StakingExecuteMsg::AddRewards{
+                amount: Uint128::from(399209u64)
+            }).unwrap()
+        }));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::FeesCollected{}).unwrap();
+        let fees: FeesCollectedResponse = from_binary(&res).unwrap();
+        assert_eq!(399209, fees.staking);
+        assert_eq!(fees.staking, fees.total);
+    }
+
+    #[test]
+    fn pause_deposits() {
+        let mut deps = mock_dependencies(&coins(2, "token"));
+
+        let msg = init_msg();
+        let info = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(20000000, "uusd"));
+        let guardian = mock_info("terra1e8ryd9ezefuucd4mje33zdms9m2s90m57878v9", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg);
+
+        let terms_msg = ExecuteMsg::Init {
+            control_variable: 300000 ,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 100000000,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), terms_msg);
+
+        // Random addresses cannot pause
+        let other = mock_info("terra1wjz4hmnrdm4ycn8mnrcjq84j4z9cxe5mmysh53", &[]);
+        let res = execute(deps.as_mut(), mock_env(), other, ExecuteMsg::Pause{ deposits: true, redemptions: false });
+        match res {
+            Err(ContractError::Unauthorized{}) => {},
+            _ => panic!("Must return unauthorized error")
+        }
+
+        execute(deps.as_mut(), mock_env(), guardian.clone(), ExecuteMsg::Pause{ deposits: true, redemptions: false }).unwrap();
+
+        let deposit_msg = ExecuteMsg::Deposit{
+            market_id: 0,
+            max_price: 500000
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), deposit_msg.clone());
+        match res {
+            Err(ContractError::Paused{}) => {},
+            _ => panic!("Must return paused error")
+        }
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::PauseStatus{}).unwrap();
+        let status: PauseStatusResponse = from_binary(&res).unwrap();
+        assert!(status.deposits_paused);
+        assert!(!status.redemptions_paused);
+
+        // Only the admin lifts the pause
+        let res = execute(deps.as_mut(), mock_env(), guardian, ExecuteMsg::Unpause{ deposits: true, redemptions: false });
+        match res {
+            Err(ContractError::Unauthorized{}) => {},
+            _ => panic!("Must return unauthorized error")
+        }
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Unpause{ deposits: true, redemptions: false }).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), deposit_msg).unwrap();
+    }
+
+    #[test]
+    fn pause_redemptions() {
+        let mut deps = mock_dependencies(&coins(2, "token"));
+
+        let msg = init_msg();
+        let info = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(20000000, "uusd"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg);
+
+        let terms_msg = ExecuteMsg::Init {
+            control_variable: 300000 ,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 100000000,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), terms_msg);
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 }).unwrap();
+
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Pause{ deposits: false, redemptions: true }).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::AllowVestedRedemptions{ allowed: true }).unwrap();
+
+        // Half way through vesting the bond is still locked
+        let mut env = mock_env();
+        env.block.height += 14400;
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::Redeem{ market_id: 0, stake: false, amount: None });
+        match res {
+            Err(ContractError::Paused{}) => {},
+            _ => panic!("Must return paused error")
+        }
+
+        // Fully vested bonds can still be redeemed
+        env.block.height += 14400;
+        execute(deps.as_mut(), env, info.clone(), ExecuteMsg::Redeem{ market_id: 0, stake: false, amount: None }).unwrap();
+    }
+
+    #[test]
+    fn admin_transfer() {
+        let mut deps = mock_dependencies(&[]);
+
+        let admin = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &[]);
+        let multisig = mock_info("terra1wjz4hmnrdm4ycn8mnrcjq84j4z9cxe5mmysh53", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg());
+
+        let propose_msg = ExecuteMsg::ProposeNewAdmin{
+            address: multisig.sender.to_string(),
+            expiry: Some(Expiration::AtHeight(mock_env().block.height + 10))
+        };
+        let res = execute(deps.as_mut(), mock_env(), multisig.clone(), propose_msg.clone());
+        match res {
+            Err(ContractError::Unauthorized{}) => {},
+            _ => panic!("Must return unauthorized error")
+        }
+        execute(deps.as_mut(), mock_env(), admin.clone(), propose_msg.clone()).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Admin{}).unwrap();
+        let value: AdminResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(admin.sender.to_string()), value.admin);
+        assert_eq!(Some(multisig.sender.to_string()), value.pending_admin);
+
+        // The proposal cannot be accepted once it expired
+        let mut env = mock_env();
+        env.block.height += 10;
+        let res = execute(deps.as_mut(), env, multisig.clone(), ExecuteMsg::AcceptAdmin{});
+        match res {
+            Err(ContractError::AdminProposalExpired{}) => {},
+            _ => panic!("Must return admin proposal expired error")
+        }
+
+        // Only the proposed address can accept
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::AcceptAdmin{});
+        match res {
+            Err(ContractError::Unauthorized{}) => {},
+            _ => panic!("Must return unauthorized error")
+        }
+        execute(deps.as_mut(), mock_env(), multisig.clone(), ExecuteMsg::AcceptAdmin{}).unwrap();
+
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::SetStaking{ staking: admin.sender.to_string() });
+        match res {
+            Err(ContractError::Unauthorized{}) => {},
+            _ => panic!("Must return unauthorized error")
+        }
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Admin{}).unwrap();
+        let value: AdminResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(multisig.sender.to_string()), value.admin);
+        assert_eq!(None, value.pending_admin);
+
+        let res = execute(deps.as_mut(), mock_env(), multisig.clone(), ExecuteMsg::AcceptAdmin{});
+        match res {
+            Err(ContractError::NoPendingAdmin{}) => {},
+            _ => panic!("Must return no pending admin error")
+        }
+    }
+
+    #[test]
+    fn timelocked_changes() {
+        let mut deps = mock_dependencies(&[]);
+
+        let admin = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &[]);
+        let anyone = mock_info("terra1wjz4hmnrdm4ycn8mnrcjq84j4z9cxe5mmysh53", &[]);
+        let mut msg = init_msg();
+        msg.timelock = Some(100);
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), msg);
+
+        let terms_msg = ExecuteMsg::Init {
+            control_variable: 300000 ,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 100000000,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+
+        };
+        execute(deps.as_mut(), mock_env(), admin.clone(), terms_msg.clone()).unwrap();
+
+        // Once terms exist, direct changes are refused
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), terms_msg);
+        match res {
+            Err(ContractError::TimelockActive{}) => {},
+            _ => panic!("Must return timelock active error")
+        }
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::SetStaking{ staking: anyone.sender.to_string() });
+        match res {
+            Err(ContractError::TimelockActive{}) => {},
+            _ => panic!("Must return timelock active error")
+        }
+
+        let new_terms = Terms{
+            control_variable: 300000,
+            vesting_term: 57600,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 500,
+            max_debt: 1000000000000000,
+            fixed_expiry: None,
+            vesting_unit: VestingUnit::Blocks,
+            cliff: 0,
+            curve: VestingCurve::Linear,
+            wrapped_payout: false,
+            pricing: PricingModel::default()
+        };
+        let propose_msg = ExecuteMsg::ProposeChange{ change: ParamChange::Terms{ market_id: 0, terms: new_terms.clone() } };
+        let res = execute(deps.as_mut(), mock_env(), anyone.clone(), propose_msg.clone());
+        match res {
+            Err(ContractError::Unauthorized{}) => {},
+            _ => panic!("Must return unauthorized error")
+        }
+        execute(deps.as_mut(), mock_env(), admin.clone(), propose_msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::PendingChanges{}).unwrap();
+        let pending: PendingChangesResponse = from_binary(&res).unwrap();
+        assert_eq!(1, pending.changes.len());
+        assert_eq!(0, pending.changes[0].id);
+        assert_eq!(mock_env().block.height + 100, pending.changes[0].executable_at);
+
+        let res = execute(deps.as_mut(), mock_env(), anyone.clone(), ExecuteMsg::ExecuteChange{ id: 0 });
+        match res {
+            Err(ContractError::ChangeLocked{ executable_at }) => assert_eq!(mock_env().block.height + 100, executable_at),
+            _ => panic!("Must return change locked error")
+        }
+
+        // Anyone can execute the change after the delay, debt accounting is untouched
+        let mut env = mock_env();
+        env.block.height += 100;
+        execute(deps.as_mut(), env.clone(), anyone.clone(), ExecuteMsg::ExecuteChange{ id: 0 }).unwrap();
+        let market = default_market(deps.as_ref());
+        assert_eq!(new_terms, market.terms);
+        assert_eq!(100000000, market.total_debt);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::PendingChanges{}).unwrap();
+        let pending: PendingChangesResponse = from_binary(&res).unwrap();
+        assert_eq!(0, pending.changes.len());
+
+        // Cancelled changes can no longer be executed
+        let propose_msg = ExecuteMsg::ProposeChange{ change: ParamChange::Timelock{ blocks: 0 } };
+        execute(deps.as_mut(), mock_env(), admin.clone(), propose_msg).unwrap();
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::CancelChange{ id: 1 }).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), anyone.clone(), ExecuteMsg::ExecuteChange{ id: 1 });
+        assert!(res.is_err());
+
+        // Changes are checked again when executed, an expiry that has passed in the queue is refused
+        let expiring_terms = Terms{ fixed_expiry: Some(mock_env().block.time.seconds() + 50), ..new_terms.clone() };
+        let propose_msg = ExecuteMsg::ProposeChange{ change: ParamChange::Terms{ market_id: 0, terms: expiring_terms } };
+        execute(deps.as_mut(), mock_env(), admin.clone(), propose_msg).unwrap();
+        let mut late_env = env.clone();
+        late_env.block.time = late_env.block.time.plus_seconds(100);
+        let res = execute(deps.as_mut(), late_env, anyone.clone(), ExecuteMsg::ExecuteChange{ id: 2 });
+        match res {
+            Err(ContractError::InvalidTerms{ .. }) => {},
+            _ => panic!("Must return invalid terms error")
+        }
+
+        // A market closed while the change was queued keeps its terms
+        let propose_msg = ExecuteMsg::ProposeChange{ change: ParamChange::Terms{ market_id: 0, terms: new_terms } };
+        execute(deps.as_mut(), mock_env(), admin.clone(), propose_msg).unwrap();
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::CloseMarket{ market_id: 0 }).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), anyone.clone(), ExecuteMsg::ExecuteChange{ id: 3 });
+        match res {
+            Err(ContractError::MarketClosed{}) => {},
+            _ => panic!("Must return market closed error")
+        }
+
+        // Early exit is configured through the queue as well
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::SetEarlyExit{
+            penalty: 2000, destination: FeeDestination::Burn });
+        match res {
+            Err(ContractError::TimelockActive{}) => {},
+            _ => panic!("Must return timelock active error")
+        }
+        let change = ParamChange::EarlyExit{ penalty: 10001, destination: FeeDestination::Burn };
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::ProposeChange{ change });
+        match res {
+            Err(ContractError::InvalidTerms{ .. }) => {},
+            _ => panic!("Must return invalid terms error")
+        }
+        let change = ParamChange::EarlyExit{ penalty: 2000, destination: FeeDestination::Burn };
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::ProposeChange{ change }).unwrap();
+        execute(deps.as_mut(), env.clone(), anyone.clone(), ExecuteMsg::ExecuteChange{ id: 4 }).unwrap();
+        assert_eq!(EarlyExitTerms{ penalty: 2000, destination: FeeDestination::Burn }, EARLY_EXIT.load(&deps.storage).unwrap());
+
+        // So are where fees go and how many addresses a RedeemMany may cover
+        for msg in [
+            ExecuteMsg::SetFeeDestination{ destination: FeeDestination::Staking },
+            ExecuteMsg::SetRedeemBatchLimit{ limit: 5 }
+        ] {
+            match execute(deps.as_mut(), mock_env(), admin.clone(), msg) {
+                Err(ContractError::TimelockActive{}) => {},
+                _ => panic!("Must return timelock active error")
+            }
+        }
+        let change = ParamChange::FeeDestination{ destination: FeeDestination::Staking };
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::ProposeChange{ change }).unwrap();
+        let change = ParamChange::RedeemBatchLimit{ limit: 5 };
+        execute(deps.as_mut(), mock_env(), admin, ExecuteMsg::ProposeChange{ change }).unwrap();
+        execute(deps.as_mut(), env.clone(), anyone.clone(), ExecuteMsg::ExecuteChange{ id: 5 }).unwrap();
+        execute(deps.as_mut(), env, anyone, ExecuteMsg::ExecuteChange{ id: 6 }).unwrap();
+        assert_eq!(FeeDestination::Staking, STATE.load(&deps.storage).unwrap().fee_destination);
+        assert_eq!(5, REDEEM_BATCH_LIMIT.load(&deps.storage).unwrap());
+    }
+
+    #[test]
+    fn set_bond_terms() {
+        let mut deps = mock_dependencies(&[]);
+
+        let admin = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg());
+
+        let initial_debt = 100000000;
+        let terms_msg = ExecuteMsg::Init {
+            control_variable: 300000 ,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 1,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+
+        };
+        execute(deps.as_mut(), mock_env(), admin.clone(), terms_msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height += 1;
+        let set_msg = ExecuteMsg::SetBondTerms{ market_id: 0, parameter: BondParameter::Vesting, value: 57600 };
+        execute(deps.as_mut(), env.clone(), admin.clone(), set_msg).unwrap();
+
+        // Only the vesting term moved, debt and decay are kept
+        let market = default_market(deps.as_ref());
+        assert_eq!(57600, market.terms.vesting_term);
+        assert_eq!(1000, market.terms.fee);
+        assert_eq!(initial_debt, market.total_debt);
+        assert_eq!(mock_env().block.height, market.last_decay);
+
+        for (parameter, value) in [
+            (BondParameter::Vesting, 9999),
+            (BondParameter::MaxPayout, 2),
+            (BondParameter::Fee, 10001)
+        ] {
+            let res = execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::SetBondTerms{ market_id: 0, parameter, value });
+            match res {
+                Err(ContractError::InvalidTerms{ .. }) => {},
+                _ => panic!("Must return invalid terms error")
+            }
+        }
+
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::SetBondTerms{ market_id: 0, parameter: BondParameter::Fee, value: 10000 }).unwrap();
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::SetBondTerms{ market_id: 0, parameter: BondParameter::Debt, value: 5 }).unwrap();
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::SetBondTerms{ market_id: 0, parameter: BondParameter::MinimumPrice, value: 20000 }).unwrap();
+        let terms = default_market(deps.as_ref()).terms;
+        assert_eq!(10000, terms.fee);
+        assert_eq!(5, terms.max_debt);
+        assert_eq!(20000, terms.minimum_price);
+
+        let other = mock_info("terra1wjz4hmnrdm4ycn8mnrcjq84j4z9cxe5mmysh53", &[]);
+        let res = execute(deps.as_mut(), env, other, ExecuteMsg::SetBondTerms{ market_id: 0, parameter: BondParameter::Fee, value: 0 });
+        match res {
+            Err(ContractError::Unauthorized{}) => {},
+            _ => panic!("Must return unauthorized error")
+        }
+    }
+
+    #[test]
+    fn initialization_guard() {
+        let mut deps = mock_dependencies(&[]);
+
+        let admin = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(20000000, "uusd"));
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg());
+
+        for msg in [
+            ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 },
+            ExecuteMsg::Redeem{ market_id: 0, stake: false, amount: None },
+            ExecuteMsg::SetBondTerms{ market_id: 0, parameter: BondParameter::Fee, value: 0 },
+            ExecuteMsg::SetAdjustment{ market_id: 0, addition: true, increment: 1, target: 1, buffer: 1 }
+        ] {
+            let res = execute(deps.as_mut(), mock_env(), admin.clone(), msg);
+            match res {
+                Err(ContractError::NotInitialized{}) => {},
+                _ => panic!("Must return not initialized error")
+            }
+        }
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Deposit{ market_id: 1, max_price: 500000 });
+        match res {
+            Err(ContractError::UnknownMarket{ market_id: 1 }) => {},
+            _ => panic!("Must return unknown market error")
+        }
+
+        let terms_msg = ExecuteMsg::Init {
+            control_variable: 300000 ,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 100000000,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+
+        };
+        execute(deps.as_mut(), mock_env(), admin.clone(), terms_msg.clone()).unwrap();
+        assert!(MARKETS.has(&deps.storage, U64Key::new(0)));
+
+        // Debt is outstanding, so the terms cannot be reset
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), terms_msg.clone());
+        match res {
+            Err(ContractError::AlreadyInitialized{}) => {},
+            _ => panic!("Must return already initialized error")
+        }
+
+        // Once the debt decayed away over a vesting term Init is allowed again
+        let mut env = mock_env();
+        env.block.height += 28800;
+        assert_eq!(0, current_debt(&default_market(deps.as_ref()), env.clone()));
+        execute(deps.as_mut(), env, admin, terms_msg).unwrap();
+    }
+
+    #[test]
+    fn selectable_pricing() {
+        let mut deps = mock_dependencies(&[]);
+
+        let admin = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg());
+
+        let mut terms_msg = ExecuteMsg::Init {
+            control_variable: 300000 ,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 100000000,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: Some(PricingModel::Fixed(FixedPrice{ price: 0 }))
+        };
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), terms_msg.clone());
+        match res {
+            Err(ContractError::InvalidTerms{ .. }) => {},
+            _ => panic!("Must return invalid terms error")
+        }
+
+        if let ExecuteMsg::Init{ ref mut pricing, .. } = terms_msg {
+            *pricing = Some(PricingModel::Fixed(FixedPrice{ price: 25000 }));
+        }
+        execute(deps.as_mut(), mock_env(), admin, terms_msg).unwrap();
+
+        // The debt ratio no longer moves the price
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::BondPrice{ market_id: 0 }).unwrap();
+        let price: BondPriceResponse = from_binary(&res).unwrap();
+        assert_eq!(25000, price.price);
+    }
+
+    #[test]
+    fn sequential_dutch_auction() {
+        let mut deps = mock_dependencies(&[]);
+
+        let admin = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(20000000, "uusd"));
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg());
+
+        let start = mock_env().block.time.seconds();
+        let market = SequentialDutchAuction{
+            capacity: 1000000000000,
+            initial_price: 50000,
+            min_price: 10000,
+            start,
+            conclusion: start + 5 * 86400,
+            deposit_interval: 3600,
+            debt_decay_interval: 86400
+        };
+        let terms_msg = ExecuteMsg::Init {
+            control_variable: 300000 ,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 0,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: Some(PricingModel::SequentialDutchAuction(market))
+        };
+        execute(deps.as_mut(), mock_env(), admin.clone(), terms_msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::MarketPrice{ market_id: 0 }).unwrap();
+        let price: MarketPriceResponse = from_binary(&res).unwrap();
+        assert_eq!(50000, price.price);
+
+        // Half a day without sales halves the price
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(43200);
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::MarketPrice{ market_id: 0 }).unwrap();
+        let price: MarketPriceResponse = from_binary(&res).unwrap();
+        assert_eq!(25000, price.price);
+
+        let depositor = mock_info("terra1q0fdx7yu5f6ddx5kckh4m5w4r9ytxvm0jrzfz8", &coins(15000000, "uusd"));
+        execute(deps.as_mut(), env.clone(), depositor.clone(), ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 }).unwrap();
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::MarketInfo{ market_id: 0 }).unwrap();
+        let info: MarketInfoResponse = from_binary(&res).unwrap();
+        assert!(info.active);
+        assert_eq!(start, info.start);
+        assert!(info.sold > 0);
+        assert_eq!(15000000000, info.purchased);
+        assert!(info.price > 25000);
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::CurrentCapacity{ market_id: 0 }).unwrap();
+        let capacity: CurrentCapacityResponse = from_binary(&res).unwrap();
+        assert_eq!(1000000000000 - info.sold, capacity.capacity);
+
+        // No deposits after the conclusion
+        env.block.time = env.block.time.plus_seconds(5 * 86400);
+        let res = execute(deps.as_mut(), env, depositor, ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 });
+        match res {
+            Err(ContractError::MarketClosed{}) => {},
+            _ => panic!("Must return market closed error")
+        }
+    }
+
+    #[test]
+    fn multiple_markets() {
+        let mut deps = mock_dependencies(&[]);
+
+        let admin = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(20000000, "uluna"));
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg());
+
+        let terms_msg = ExecuteMsg::Init {
+            control_variable: 300000 ,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 100000000,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+        };
+        execute(deps.as_mut(), mock_env(), admin.clone(), terms_msg).unwrap();
+
+        let create_msg = ExecuteMsg::CreateMarket {
+            quote_denom: "uluna".to_string(),
+            control_variable: 300000 ,
+            vesting_term: 57600,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 100000000,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+        };
+        let other = mock_info("terra1wjz4hmnrdm4ycn8mnrcjq84j4z9cxe5mmysh53", &[]);
+        let res = execute(deps.as_mut(), mock_env(), other, create_msg.clone());
+        match res {
+            Err(ContractError::Unauthorized{}) => {},
+            _ => panic!("Must return unauthorized error")
+        }
+        execute(deps.as_mut(), mock_env(), admin.clone(), create_msg).unwrap();
+
+        // Deposits are paid in the quote asset of the market and only move its debt
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Deposit{ market_id: 1, max_price: 500000 }).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+            funds: coins(20000000, "uluna"),
+            contract_addr: "terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8".to_string(),
+            msg: to_binary(&TreasuryExecuteMsg::Deposit{
+                amount: 20000000000,
+                profit: 20000000000 - 39920900 - 399209
+            }).unwrap()
+        }));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::State{ market_id: 1 }).unwrap();
+        let state: StateResponse = from_binary(&res).unwrap();
+        assert_eq!(100000000 + 20000000000, state.total_debt);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::State{ market_id: 0 }).unwrap();
+        let state: StateResponse = from_binary(&res).unwrap();
+        assert_eq!(100000000, state.total_debt);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::BondInfo{ address: admin.sender.to_string(), market_id: 1 }).unwrap();
+        let bond: BondInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(39920900, bond.payout);
+        assert_eq!(57600, bond.vesting);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Markets{ start_after: None, limit: None }).unwrap();
+        let markets: MarketsResponse = from_binary(&res).unwrap();
+        assert_eq!(2, markets.markets.len());
+        assert_eq!("uusd", markets.markets[0].quote_denom);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Markets{ start_after: Some(0), limit: Some(1) }).unwrap();
+        let markets: MarketsResponse = from_binary(&res).unwrap();
+        assert_eq!(1, markets.markets.len());
+        assert_eq!(1, markets.markets[0].id);
+        assert_eq!("uluna", markets.markets[0].quote_denom);
+
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::CloseMarket{ market_id: 1 }).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Deposit{ market_id: 1, max_price: 500000 });
+        match res {
+            Err(ContractError::MarketClosed{}) => {},
+            _ => panic!("Must return market closed error")
+        }
+
+        let res = execute(deps.as_mut(), mock_env(), admin, ExecuteMsg::Deposit{ market_id: 2, max_price: 500000 });
+        match res {
+            Err(ContractError::UnknownMarket{ market_id: 2 }) => {},
+            _ => panic!("Must return unknown market error")
+        }
+    }
+
+    #[test]
+    fn fixed_expiry() {
+        let mut deps = mock_dependencies(&[]);
+
+        let admin = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(20000000, "uusd"));
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg());
+
+        let expiry = mock_env().block.time.seconds() + 10 * 86400;
+        let mut terms_msg = ExecuteMsg::Init {
+            control_variable: 300000 ,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 100000000,
+            fixed_expiry: Some(mock_env().block.time.seconds()),
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+        };
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), terms_msg.clone());
+        match res {
+            Err(ContractError::InvalidTerms{ .. }) => {},
+            _ => panic!("Must return invalid terms error")
+        }
+        if let ExecuteMsg::Init{ ref mut fixed_expiry, .. } = terms_msg {
+            *fixed_expiry = Some(expiry);
+        }
+        execute(deps.as_mut(), mock_env(), admin.clone(), terms_msg).unwrap();
+
+        // A deposit half way to the expiry vests over the remaining five days
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(5 * 86400);
+        env.block.height += 1;
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 }).unwrap();
+
+        let address = admin.sender.to_string();
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::BondInfo{ address: address.clone(), market_id: 0 }).unwrap();
+        let bond: BondInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(expiry), bond.expiry);
+        assert_eq!(5 * 86400, bond.vesting);
+
+        // Block height no longer matters, only the time left until the expiry
+        env.block.time = env.block.time.plus_seconds(86400);
+        env.block.height += 100000;
+        assert_eq!(2000, percent_vested_for(deps.as_ref(), env.clone(), address.clone(), 0).unwrap());
+        assert_eq!(bond.payout / 5, pending_payout(deps.as_ref(), env.clone(), address.clone(), 0).unwrap());
+
+        env.block.time = env.block.time.plus_seconds(4 * 86400);
+        assert_eq!(10000, percent_vested_for(deps.as_ref(), env.clone(), address.clone(), 0).unwrap());
+        assert_eq!(bond.payout, pending_payout(deps.as_ref(), env.clone(), address, 0).unwrap());
+
+        let res = execute(deps.as_mut(), env, admin, ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 });
+        match res {
+            Err(ContractError::MarketClosed{}) => {},
+            _ => panic!("Must return market closed error")
+        }
+    }
+
+    #[test]
+    fn time_based_vesting() {
+        let mut deps = mock_dependencies(&[]);
+
+        let admin = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(15000000, "uusd"));
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg());
+
+        let mut terms_msg = ExecuteMsg::Init {
+            control_variable: 300000 ,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 0,
+            fixed_expiry: None,
+            vesting_unit: Some(VestingUnit::Seconds),
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+        };
+        execute(deps.as_mut(), mock_env(), admin.clone(), terms_msg.clone()).unwrap();
+
+        // Seconds markets are held to their own minimum term
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::SetBondTerms{
+            market_id: 0, parameter: BondParameter::Vesting, value: MIN_VESTING_TERM });
+        match res {
+            Err(ContractError::InvalidTerms{ .. }) => {},
+            _ => panic!("Must return invalid terms error")
+        }
+        if let ExecuteMsg::Init{ ref mut vesting_term, .. } = terms_msg {
+            *vesting_term = 5 * 86400;
+        }
+        execute(deps.as_mut(), mock_env(), admin.clone(), terms_msg).unwrap();
+
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 }).unwrap();
+        let address = admin.sender.to_string();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::BondInfo{ address: address.clone(), market_id: 0 }).unwrap();
+        let bond: BondInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(VestingUnit::Seconds, bond.vesting_unit);
+        assert_eq!(5 * 86400, bond.vesting);
+
+        // Half way through the term no matter how many blocks were produced
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(5 * 43200);
+        env.block.height += 10;
+        assert_eq!(5000, percent_vested_for(deps.as_ref(), env.clone(), address.clone(), 0).unwrap());
+        assert_eq!(bond.payout / 2, pending_payout(deps.as_ref(), env.clone(), address.clone(), 0).unwrap());
+        assert_eq!(7500000000, current_debt(&default_market(deps.as_ref()), env.clone()));
+
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::Redeem{ market_id: 0, stake: false, amount: None }).unwrap();
+        let res = query(deps.as_ref(), env, QueryMsg::BondInfo{ address, market_id: 0 }).unwrap();
+        let bond: BondInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(5 * 43200, bond.vesting);
+    }
+
+    #[test]
+    fn debt_decay_by_unit() {
+        let mut deps = mock_dependencies(&[]);
+
+        let admin = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg());
+        let terms_msg = |vesting_term: u64, vesting_unit: VestingUnit| ExecuteMsg::CreateMarket {
+            quote_denom: "uusd".to_string(),
+            control_variable: 300000 ,
+            vesting_term,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 100000000,
+            fixed_expiry: None,
+            vesting_unit: Some(vesting_unit),
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+        };
+        execute(deps.as_mut(), mock_env(), admin.clone(), terms_msg(28800, VestingUnit::Blocks)).unwrap();
+        execute(deps.as_mut(), mock_env(), admin, terms_msg(172800, VestingUnit::Seconds)).unwrap();
+        let current_debt = |deps: Deps, env: Env, market_id: u64| -> u64 {
+            let res = query(deps, env, QueryMsg::CurrentDebt{ market_id }).unwrap();
+            let debt: CurrentDebtResponse = from_binary(&res).unwrap();
+            debt.current_debt
+        };
+
+        // Both units run the debt off over one vesting term
+        let mut env = mock_env();
+        env.block.height += 1;
+        env.block.time = env.block.time.plus_seconds(6);
+        assert_eq!(99996528, current_debt(deps.as_ref(), env.clone(), 0));
+        assert_eq!(99996528, current_debt(deps.as_ref(), env.clone(), 1));
+        env.block.height += 14399;
+        env.block.time = env.block.time.plus_seconds(86394);
+        assert_eq!(50000000, current_debt(deps.as_ref(), env.clone(), 0));
+        assert_eq!(50000000, current_debt(deps.as_ref(), env.clone(), 1));
+        env.block.height += 28800;
+        env.block.time = env.block.time.plus_seconds(172800);
+        assert_eq!(0, current_debt(deps.as_ref(), env.clone(), 0));
+        assert_eq!(0, current_debt(deps.as_ref(), env, 1));
+    }
+
+    #[test]
+    fn migrate_single_market() {
+        let mut deps = mock_dependencies(&[]);
+        let admin = "terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8";
+        let holder = Addr::unchecked("terra1e8ryd9ezefuucd4mje33zdms9m2s90m57878v9");
+
+        // Storage as the single market contract left it
+        let height = mock_env().block.height;
+        deps.storage.set(b"bond_admin", format!("\"{}\"", admin).as_bytes());
+        deps.storage.set(b"state", format!(
+            r#"{{"treasury":"{0}","dao":"{0}","staking":"{0}","total_debt":100000000,"last_decay":{1}}}"#,
+            admin, height - 100).as_bytes());
+        deps.storage.set(b"terms", br#"{"control_variable":300000,"vesting_term":28800,"minimum_price":10000,"max_payout":50,"fee":1000,"max_debt":1000000000000000}"#);
+        deps.storage.set(b"adjust", format!(
+            r#"{{"add":true,"rate":10,"target":400000,"buffer":100,"last_block":{}}}"#, height - 50).as_bytes());
+        LEGACY_BOND.save(&mut deps.storage, &holder, &LegacyBond{
+            payout: 300000000,
+            vesting: 28800,
+            last_block: height - 9600,
+            price_paid: 10000
+        }).unwrap();
+        LEGACY_BOND.save(&mut deps.storage, &Addr::unchecked(admin), &LegacyBond{
+            payout: 600000000,
+            vesting: 28800,
+            last_block: height,
+            price_paid: 10000
+        }).unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg{ seconds_markets: vec![], seconds_per_block: 6 }).unwrap();
+
+        let state = STATE.load(&deps.storage).unwrap();
+        assert_eq!(FeeDestination::Dao, state.fee_destination);
+        assert_eq!(0, state.timelock);
+        let market = default_market(deps.as_ref());
+        assert_eq!("uusd", market.quote_denom);
+        assert_eq!(28800, market.terms.vesting_term);
+        assert_eq!(VestingUnit::Blocks, market.terms.vesting_unit);
+        assert_eq!(100000000, market.total_debt);
+        assert_eq!(height - 100, market.last_decay);
+        assert_eq!(mock_env().block.time.seconds() - 600, market.last_decay_time);
+        let adjust = market.adjust.unwrap();
+        assert_eq!(400000, adjust.target);
+        assert_eq!(mock_env().block.time.seconds() - 300, adjust.last_time);
+        assert!(LEGACY_TERMS.may_load(&deps.storage).unwrap().is_none());
+        assert_eq!(0, LEGACY_BOND.range(&deps.storage, None, None, Order::Ascending).count());
+
+        // Positions carry on vesting in market 0 and are indexed like new ones
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::BondInfo{ address: holder.to_string(), market_id: 0 }).unwrap();
+        let bond: BondInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(300000000, bond.payout);
+        assert_eq!(height - 9600, bond.last_block);
+        assert_eq!(3333, percent_vested_for(deps.as_ref(), mock_env(), holder.to_string(), 0).unwrap());
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::AllBonds{ start_after: None, limit: None }).unwrap();
+        let all: AllBondsResponse = from_binary(&res).unwrap();
+        assert_eq!(2, all.bonds.len());
+        assert_eq!(2, BOND_COUNT.load(&deps.storage).unwrap());
+        let mut env = mock_env();
+        env.block.height += 19200;
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::MaturedBonds{ start_after: None, limit: None }).unwrap();
+        let matured: MaturedBondsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![holder.to_string()], matured.bonds.iter().map(|bond| bond.owner.clone()).collect::<Vec<_>>());
+
+        let res = execute(deps.as_mut(), env.clone(), mock_info(holder.as_str(), &[]), ExecuteMsg::Redeem{ market_id: 0, stake: false, amount: None }).unwrap();
+        assert_eq!(1, res.messages.len());
+
+        // The admin can still pause, there is no guardian until one is set
+        execute(deps.as_mut(), env.clone(), mock_info(admin, &[]), ExecuteMsg::Pause{ deposits: true, redemptions: false }).unwrap();
+        let res = query(deps.as_ref(), env, QueryMsg::PauseStatus{}).unwrap();
+        let status: PauseStatusResponse = from_binary(&res).unwrap();
+        assert_eq!(None, status.guardian);
+        assert!(status.deposits_paused);
+    }
+
+    #[test]
+    fn migrate_positions() {
+        let mut deps = mock_dependencies(&[]);
+
+        let admin = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(15000000, "uusd"));
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg());
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Init {
+            control_variable: 300000 ,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 0,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+        }).unwrap();
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 }).unwrap();
+
+        let mut env = mock_env();
+        env.block.height += 9600;
+        env.block.time = env.block.time.plus_seconds(9600 * 6);
+        let address = admin.sender.to_string();
+        assert_eq!(3333, percent_vested_for(deps.as_ref(), env.clone(), address.clone(), 0).unwrap());
+
+        let res = migrate(deps.as_mut(), env.clone(), MigrateMsg{ seconds_markets: vec![0], seconds_per_block: 6 });
+        assert!(res.is_ok());
+        let res = migrate(deps.as_mut(), env.clone(), MigrateMsg{ seconds_markets: vec![1], seconds_per_block: 6 });
+        match res {
+            Err(ContractError::UnknownMarket{ market_id: 1 }) => {},
+            _ => panic!("Must return unknown market error")
+        }
+
+        let market = default_market(deps.as_ref());
+        assert_eq!(VestingUnit::Seconds, market.terms.vesting_unit);
+        assert_eq!(172800, market.terms.vesting_term);
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::BondInfo{ address: address.clone(), market_id: 0 }).unwrap();
+        let bond: BondInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(VestingUnit::Seconds, bond.vesting_unit);
+        assert_eq!(172800, bond.vesting);
+
+        // Existing positions keep their progress and now vest with time
+        assert_eq!(3333, percent_vested_for(deps.as_ref(), env.clone(), address.clone(), 0).unwrap());
+        env.block.height += 1;
+        env.block.time = env.block.time.plus_seconds(9600 * 6);
+        assert_eq!(6666, percent_vested_for(deps.as_ref(), env, address, 0).unwrap());
+    }
+
+    #[test]
+    fn vesting_cliff_and_curve() {
+        let mut deps = mock_dependencies(&[]);
+
+        let admin = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(15000000, "uusd"));
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg());
+
+        let day = 86400;
+        let mut terms_msg = ExecuteMsg::Init {
+            control_variable: 300000 ,
+            vesting_term: 360 * day,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 0,
+            fixed_expiry: None,
+            vesting_unit: Some(VestingUnit::Seconds),
+            cliff: Some(361 * day),
+            curve: Some(VestingCurve::Step{ interval: 0 }),
+            wrapped_payout: None,
+            pricing: None
+        };
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), terms_msg.clone());
+        match res {
+            Err(ContractError::InvalidTerms{ .. }) => {},
+            _ => panic!("Must return invalid terms error")
+        }
+        if let ExecuteMsg::Init{ ref mut cliff, .. } = terms_msg {
+            *cliff = Some(90 * day);
+        }
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), terms_msg.clone());
+        match res {
+            Err(ContractError::InvalidTerms{ .. }) => {},
+            _ => panic!("Must return invalid terms error")
+        }
+        // Monthly steps after a three month cliff
+        if let ExecuteMsg::Init{ ref mut curve, .. } = terms_msg {
+            *curve = Some(VestingCurve::Step{ interval: 30 * day });
+        }
+        execute(deps.as_mut(), mock_env(), admin.clone(), terms_msg).unwrap();
+
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 }).unwrap();
+        let address = admin.sender.to_string();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::BondInfo{ address: address.clone(), market_id: 0 }).unwrap();
+        let bond: BondInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(mock_env().block.time.seconds() + 90 * day, bond.cliff_end);
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(89 * day);
+        assert_eq!(0, percent_vested_for(deps.as_ref(), env.clone(), address.clone(), 0).unwrap());
+        assert_eq!(0, pending_payout(deps.as_ref(), env.clone(), address.clone(), 0).unwrap());
+
+        env.block.time = env.block.time.plus_seconds(day);
+        assert_eq!(2500, percent_vested_for(deps.as_ref(), env.clone(), address.clone(), 0).unwrap());
+        env.block.time = env.block.time.plus_seconds(10 * day);
+        assert_eq!(2500, percent_vested_for(deps.as_ref(), env.clone(), address.clone(), 0).unwrap());
+        assert_eq!(bond.payout / 4, pending_payout(deps.as_ref(), env.clone(), address.clone(), 0).unwrap());
+
+        // Redeeming mid step keeps the remaining steps on the original schedule
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::Redeem{ market_id: 0, stake: false, amount: None }).unwrap();
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::BondInfo{ address: address.clone(), market_id: 0 }).unwrap();
+        let remaining: BondInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(bond.payout - bond.payout / 4, remaining.payout);
+        assert_eq!(270 * day, remaining.vesting);
+        assert_eq!(0, percent_vested_for(deps.as_ref(), env.clone(), address.clone(), 0).unwrap());
+
+        env.block.time = env.block.time.plus_seconds(20 * day);
+        assert_eq!(1111, percent_vested_for(deps.as_ref(), env.clone(), address.clone(), 0).unwrap());
+        env.block.time = env.block.time.plus_seconds(240 * day);
+        assert_eq!(10000, percent_vested_for(deps.as_ref(), env, address, 0).unwrap());
+    }
+
+    #[test]
+    fn partial_redemption() {
+        let mut deps = mock_dependencies(&[]);
+
+        let admin = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(15000000, "uusd"));
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg());
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Init {
+            control_variable: 300000 ,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 0,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+        }).unwrap();
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 }).unwrap();
+        let address = admin.sender.to_string();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::BondInfo{ address: address.clone(), market_id: 0 }).unwrap();
+        let bond: BondInfoResponse = from_binary(&res).unwrap();
+
+        let mut env = mock_env();
+        env.block.height += 14400;
+        let half = bond.payout / 2;
+        let res = execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::Redeem{
+            market_id: 0, stake: false, amount: Some(Uint128::from(half + 1)) });
+        match res {
+            Err(ContractError::ExceedsVested{ vested }) => assert_eq!(half, vested),
+            _ => panic!("Must return exceeds vested error")
+        }
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::Redeem{
+            market_id: 0, stake: false, amount: Some(Uint128::from(half / 2)) }).unwrap();
+
+        // The unclaimed part stays available, the rest keeps vesting on the original schedule
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::BondInfo{ address: address.clone(), market_id: 0 }).unwrap();
+        let remaining: BondInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(bond.payout - half, remaining.payout);
+        assert_eq!(half - half / 2, remaining.claimable);
+        assert_eq!(14400, remaining.vesting);
+        assert_eq!(bond.last_block + 14400, remaining.last_block);
+        assert_eq!(remaining.claimable, pending_payout(deps.as_ref(), env.clone(), address.clone(), 0).unwrap());
+
+        env.block.height += 7200;
+        assert_eq!(remaining.claimable + remaining.payout / 2,
+            pending_payout(deps.as_ref(), env.clone(), address.clone(), 0).unwrap());
+
+        env.block.height += 7200;
+        assert_eq!(remaining.claimable + remaining.payout,
+            pending_payout(deps.as_ref(), env.clone(), address.clone(), 0).unwrap());
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::Redeem{ market_id: 0, stake: false, amount: None }).unwrap();
+        let res = query(deps.as_ref(), env, QueryMsg::BondInfo{ address, market_id: 0 }).unwrap();
+        let redeemed: BondInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(0, redeemed.payout);
+        assert_eq!(0, redeemed.claimable);
+    }
+
+    #[test]
+    fn empty_redemptions() {
+        let mut deps = mock_dependencies(&[]);
+
+        let admin = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(15000000, "uusd"));
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg());
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Init {
+            control_variable: 300000 ,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 0,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+        }).unwrap();
+
+        // Addresses without a position get an error instead of aborting
+        let stranger = mock_info("terra1q0fdx7yu5f6ddx5kckh4m5w4r9ytxvm0jrzfz8", &[]);
+        let res = execute(deps.as_mut(), mock_env(), stranger.clone(), ExecuteMsg::Redeem{ market_id: 0, stake: false, amount: None });
+        match res {
+            Err(ContractError::NoBond{}) => {},
+            _ => panic!("Must return no bond error")
+        }
+        assert_eq!(0, percent_vested_for(deps.as_ref(), mock_env(), stranger.sender.to_string(), 0).unwrap());
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::BondInfo{ address: stranger.sender.to_string(), market_id: 0 }).unwrap();
+        let bond: BondInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(BondInfoResponse::default(), bond);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::PendingPayout{ address: "".to_string(), market_id: 0 });
+        assert!(res.is_err());
+
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 }).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Redeem{ market_id: 0, stake: false, amount: None });
+        match res {
+            Err(ContractError::NothingToRedeem{}) => {},
+            _ => panic!("Must return nothing to redeem error")
+        }
+
+        let mut env = mock_env();
+        env.block.height += 100;
+        let res = execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::Redeem{
+            market_id: 0, stake: false, amount: Some(Uint128::zero()) });
+        match res {
+            Err(ContractError::NothingToRedeem{}) => {},
+            _ => panic!("Must return nothing to redeem error")
+        }
+        execute(deps.as_mut(), env, admin, ExecuteMsg::Redeem{ market_id: 0, stake: false, amount: None }).unwrap();
+    }
+
+    #[test]
+    fn early_exit_penalty() {
+        let mut deps = mock_dependencies(&[]);
+
+        let admin = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(15000000, "uusd"));
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg());
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Init {
+            control_variable: 300000 ,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 0,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+        }).unwrap();
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 }).unwrap();
+        let address = admin.sender.to_string();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::BondInfo{ address, market_id: 0 }).unwrap();
+        let bond: BondInfoResponse = from_binary(&res).unwrap();
+
+        // Holders look up the id to exit with in their bond info
+        assert_eq!(Some(0), bond.bond_id);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::BondInfo{ address: "terra1q0fdx7yu5f6ddx5kckh4m5w4r9ytxvm0jrzfz8".to_string(), market_id: 0 }).unwrap();
+        let none: BondInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(None, none.bond_id);
+
+        let mut env = mock_env();
+        env.block.height += 14400;
+        let res = execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::EarlyExit{ bond_id: 0 });
+        match res {
+            Err(ContractError::EarlyExitDisabled{}) => {},
+            _ => panic!("Must return early exit disabled error")
+        }
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::EarlyExit{ bond_id: 0 });
+        match res {
+            Err(StdError::GenericErr{ msg, .. }) => assert_eq!(ContractError::EarlyExitDisabled{}.to_string(), msg),
+            _ => panic!("Must return early exit disabled error")
+        }
+        let res = execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::SetEarlyExit{
+            penalty: 10001, destination: FeeDestination::Dao });
+        match res {
+            Err(ContractError::InvalidTerms{ .. }) => {},
+            _ => panic!("Must return invalid terms error")
+        }
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::SetEarlyExit{
+            penalty: 2000, destination: FeeDestination::Dao }).unwrap();
+
+        // Half vested, a fifth of the other half is forfeited
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::EarlyExit{ bond_id: 0 }).unwrap();
+        let exit: EarlyExitResponse = from_binary(&res).unwrap();
+        let unvested = bond.payout - bond.payout / 2;
+        assert_eq!(bond.payout / 2, exit.vested);
+        assert_eq!(unvested, exit.unvested);
+        assert_eq!(unvested / 5, exit.penalty);
+        assert_eq!(bond.payout - unvested / 5, exit.payout);
+
+        let other = mock_info("terra1q0fdx7yu5f6ddx5kckh4m5w4r9ytxvm0jrzfz8", &[]);
+        let res = execute(deps.as_mut(), env.clone(), other, ExecuteMsg::EarlyExit{ bond_id: 0 });
+        match res {
+            Err(ContractError::Unauthorized{}) => {},
+            _ => panic!("Must return unauthorized error")
+        }
+
+        let fees = FEES_COLLECTED.load(&deps.storage).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::EarlyExit{ bond_id: 0 }).unwrap();
+        assert_eq!(2, res.messages.len());
+        // The penalty counts towards the fees collected like the deposit fee
+        assert_eq!(fees.dao + unvested / 5, FEES_COLLECTED.load(&deps.storage).unwrap().dao);
+        let res = execute(deps.as_mut(), env, admin, ExecuteMsg::EarlyExit{ bond_id: 0 });
+        match res {
+            Err(ContractError::NoBond{}) => {},
+            _ => panic!("Must return no bond error")
+        }
+    }
+
+    #[test]
+    fn redeem_into_staking() {
+        let mut deps = mock_dependencies(&[]);
+
+        let admin = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(15000000, "uusd"));
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg());
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Init {
+            control_variable: 300000 ,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 0,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+        }).unwrap();
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 }).unwrap();
+        let address = admin.sender.to_string();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::BondInfo{ address: address.clone(), market_id: 0 }).unwrap();
+        let bond: BondInfoResponse = from_binary(&res).unwrap();
+        let state = STATE.load(deps.as_ref().storage).unwrap();
+
+        let mut env = mock_env();
+        env.block.height += 14400;
+        let res = execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::Redeem{ market_id: 0, stake: false, amount: None }).unwrap();
+        assert_eq!(res.messages, vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute{
+            funds: vec![],
+            contract_addr: state.treasury.to_string(),
+            msg: to_binary(&TreasuryExecuteMsg::Transfer{
+                recipient: address.clone(),
+                amount: Uint128::from(bond.payout / 2)
+            }).unwrap()
+        }))]);
+
+        // Staked payouts are pulled by the staking contract, which may keep them in warmup
+        env.block.height += 14400;
+        let res = execute(deps.as_mut(), env, admin, ExecuteMsg::Redeem{ market_id: 0, stake: true, amount: None }).unwrap();
+        let amount = Uint128::from(bond.payout - bond.payout / 2);
+        assert_eq!(res.messages, vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute{
+                funds: vec![],
+                contract_addr: state.treasury.to_string(),
+                msg: to_binary(&TreasuryExecuteMsg::IncreaseAllowance{
+                    spender: state.staking.to_string(),
+                    amount
+                }).unwrap()
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute{
+                funds: vec![],
+                contract_addr: state.staking.to_string(),
+                msg: to_binary(&StakingExecuteMsg::Stake{
+                    recipient: address,
+                    amount
+                }).unwrap()
+            }))
+        ]);
+    }
+
+    #[test]
+    fn wrapped_payout() {
+        let mut deps = staking_dependencies(false);
+
+        let admin = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(15000000, "uusd"));
+        let wrapped_token = "terra1q0fdx7yu5f6ddx5kckh4m5w4r9ytxvm0jrzfz8".to_string();
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg());
+        let init = ExecuteMsg::Init {
+            control_variable: 300000 ,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 0,
+            max_debt: 1000000000000000,
+            initial_debt: 0,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: Some(true),
+            pricing: None
+        };
+
+        // Payouts are delivered as wsPHS, so the token has to be known first
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), init.clone());
+        match res {
+            Err(ContractError::InvalidTerms{ .. }) => {},
+            _ => panic!("Must return invalid terms error")
+        }
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::SetWrappedToken{ address: wrapped_token.clone() }).unwrap();
+
+        // Shares staked for the bond must not sit in warmup
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), init.clone());
+        match res {
+            Err(ContractError::InvalidTerms{ .. }) => {},
+            _ => panic!("Must return invalid terms error")
+        }
+        deps.querier.exempt = true;
+        execute(deps.as_mut(), mock_env(), admin.clone(), init).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Terms{ market_id: 0 }).unwrap();
+        let terms: TermsResponse = from_binary(&res).unwrap();
+        assert!(terms.wrapped_payout);
+
+        // The payout is staked by the bond, which owes the depositor the minted shares
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 }).unwrap();
+        let state = STATE.load(deps.as_ref().storage).unwrap();
+        let stake = match &res.messages[2].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute{ msg, .. }) => from_binary(msg).unwrap(),
+            _ => panic!("Must stake the payout")
+        };
+        let amount = match stake {
+            StakingExecuteMsg::Stake{ recipient, amount } => {
+                assert_eq!(MOCK_CONTRACT_ADDR, recipient);
+                amount
+            },
+            _ => panic!("Must stake the payout")
+        };
+        let address = admin.sender.to_string();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::BondInfo{ address: address.clone(), market_id: 0 }).unwrap();
+        let bond: BondInfoResponse = from_binary(&res).unwrap();
+        assert!(bond.wrapped);
+        assert_eq!(amount.u128() as u64 / 2, bond.payout);
+
+        // Losing the exemption stops new deposits instead of staking into warmup
+        deps.querier.exempt = false;
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 });
+        match res {
+            Err(ContractError::NotWarmupExempt{}) => {},
+            _ => panic!("Must return not warmup exempt error")
+        }
+        deps.querier.exempt = true;
+
+        // Shares are wrapped for the owner as they vest, whether or not the depositor asks to stake
+        let mut env = mock_env();
+        env.block.height += 14400;
+        let res = execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::Redeem{ market_id: 0, stake: false, amount: None }).unwrap();
+        assert_eq!(res.messages, vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute{
+            funds: vec![],
+            contract_addr: state.staking.to_string(),
+            msg: to_binary(&StakingExecuteMsg::SendShares{
+                contract: wrapped_token,
+                shares: Uint128::from(bond.payout / 2),
+                msg: to_binary(&WrapMsg::Wrap{ recipient: Some(address.clone()) }).unwrap()
+            }).unwrap()
+        }))]);
+
+        // Open positions are counted in shares, so the market cannot switch back
+        let mut new_terms = default_market(deps.as_ref()).terms;
+        new_terms.wrapped_payout = false;
+        let res = execute(deps.as_mut(), env, admin, ExecuteMsg::ProposeChange{
+            change: ParamChange::Terms{ market_id: 0, terms: new_terms }
+        });
+        match res {
+            Err(ContractError::InvalidTerms{ .. }) => {},
+            _ => panic!("Must return invalid terms error")
+        }
+    }
+
+    #[test]
+    fn redeem_many() {
+        let mut deps = mock_dependencies(&[]);
+
+        let admin = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(15000000, "uusd"));
+        let other = mock_info("terra1e8ryd9ezefuucd4mje33zdms9m2s90m57878v9", &coins(15000000, "uusd"));
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg());
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Init {
+            control_variable: 300,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 0,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+        }).unwrap();
+        // A low control variable keeps both deposits at the minimum price while the first one's debt decays
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 }).unwrap();
+        let mut env = mock_env();
+        env.block.height += 100;
+        execute(deps.as_mut(), env.clone(), other.clone(), ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 }).unwrap();
+
+        let addresses = vec![admin.sender.to_string(), other.sender.to_string(), "terra1q0fdx7yu5f6ddx5kckh4m5w4r9ytxvm0jrzfz8".to_string()];
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::SetRedeemBatchLimit{ limit: 2 }).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), other.clone(), ExecuteMsg::RedeemMany{ addresses: addresses.clone(), stake: false });
+        match res {
+            Err(ContractError::BatchTooLarge{ limit: 2 }) => {},
+            _ => panic!("Must return batch too large error")
+        }
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::SetRedeemBatchLimit{ limit: 3 }).unwrap();
+
+        // Anyone can push vested payouts, addresses without a bond are skipped
+        env.block.height += 14400;
+        let res = execute(deps.as_mut(), env.clone(), other.clone(), ExecuteMsg::RedeemMany{ addresses: addresses.clone(), stake: false }).unwrap();
+        assert_eq!(2, res.messages.len());
+
+        // A partly redeemed position still matures when its vesting period ends
+        let matured_bonds = |deps: Deps, env: Env, start_after: Option<MaturedCursor>, limit: Option<u32>| -> MaturedBondsResponse {
+            let res = query(deps, env, QueryMsg::MaturedBonds{ start_after, limit }).unwrap();
+            from_binary(&res).unwrap()
+        };
+        let bond_ids = |matured: &MaturedBondsResponse| -> Vec<u64> {
+            matured.bonds.iter().map(|bond| bond.bond_id).collect()
+        };
+        env.block.height += 14350;
+        assert_eq!(vec![0], bond_ids(&matured_bonds(deps.as_ref(), env.clone(), None, None)));
+        env.block.height += 50;
+        assert_eq!(vec![0, 1], bond_ids(&matured_bonds(deps.as_ref(), env.clone(), None, None)));
+        let first_page = matured_bonds(deps.as_ref(), env.clone(), None, Some(1));
+        assert_eq!(vec![0], bond_ids(&first_page));
+
+        // Redeeming the first page does not break the cursor for the next one, staked payouts go to the owners
+        let res = execute(deps.as_mut(), env.clone(), other.clone(), ExecuteMsg::RedeemMany{ addresses: vec![admin.sender.to_string()], stake: true }).unwrap();
+        assert_eq!(2, res.messages.len());
+        match &res.messages[1].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute{ msg, .. }) => match from_binary(msg).unwrap() {
+                StakingExecuteMsg::Stake{ recipient, .. } => assert_eq!(admin.sender.to_string(), recipient),
+                _ => panic!("Must stake for the owner")
+            },
+            _ => panic!("Must stake for the owner")
+        }
+        let next_page = matured_bonds(deps.as_ref(), env.clone(), first_page.next_start_after, None);
+        assert_eq!(vec![1], bond_ids(&next_page));
+
+        let res = execute(deps.as_mut(), env.clone(), other, ExecuteMsg::RedeemMany{ addresses, stake: false }).unwrap();
+        assert_eq!(1, res.messages.len());
+        assert!(bond_ids(&matured_bonds(deps.as_ref(), env.clone(), None, None)).is_empty());
+
+        // Closed positions leave nothing behind in the owner index, a new deposit opens a new id
+        let owned = bonds().idx.owner.prefix(admin.sender.clone())
+            .range(&deps.storage, None, None, Order::Ascending)
+            .count();
+        assert_eq!(0, owned);
+        execute(deps.as_mut(), env, admin.clone(), ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 }).unwrap();
+        let (key, bond) = bonds().idx.owner.item(&deps.storage, (admin.sender, U64Key::new(0))).unwrap().unwrap();
+        assert_eq!(2, bond_id(&key));
+        assert_eq!(0, bond.market_id);
+    }
+
+    #[test]
+    fn all_bonds() {
+        let mut deps = mock_dependencies(&[]);
+
+        let admin = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(15000000, "uusd"));
+        let other = mock_info("terra1e8ryd9ezefuucd4mje33zdms9m2s90m57878v9", &coins(15000000, "uusd"));
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg());
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Init {
+            control_variable: 300,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 0,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+        }).unwrap();
+        // A low control variable keeps both deposits at the minimum price while the first one's debt decays
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 }).unwrap();
+        let mut env = mock_env();
+        env.block.height += 100;
+        execute(deps.as_mut(), env.clone(), other.clone(), ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 }).unwrap();
+
+        env.block.height += 14300;
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::AllBonds{ start_after: None, limit: None }).unwrap();
+        let all: AllBondsResponse = from_binary(&res).unwrap();
+        assert_eq!(2, all.bonds.len());
+        let bond = &all.bonds[0];
+        assert_eq!(0, bond.bond_id);
+        assert_eq!(admin.sender.to_string(), bond.owner);
+        assert_eq!(14400, bond.vesting_left);
+        assert_eq!(bond.payout / 2, bond.pending_payout);
+        assert_eq!(pending_payout(deps.as_ref(), env.clone(), admin.sender.to_string(), 0).unwrap(), bond.pending_payout);
+        assert_eq!(0, bond.claimable);
+
+        // A partly redeemed position still shows what it is owed
+        let redeemed = bond.pending_payout / 2;
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::Redeem{
+            market_id: 0, stake: false, amount: Some(Uint128::from(redeemed)) }).unwrap();
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::AllBonds{ start_after: None, limit: Some(1) }).unwrap();
+        let all: AllBondsResponse = from_binary(&res).unwrap();
+        let partly_redeemed = &all.bonds[0];
+        assert_eq!(bond.payout - bond.pending_payout, partly_redeemed.payout);
+        assert_eq!(bond.pending_payout - redeemed, partly_redeemed.claimable);
+        assert_eq!(partly_redeemed.claimable, partly_redeemed.pending_payout);
+        assert_eq!(14400, partly_redeemed.vesting_left);
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::AllBonds{ start_after: Some(0), limit: Some(1) }).unwrap();
+        let all: AllBondsResponse = from_binary(&res).unwrap();
+        assert_eq!(1, all.bonds.len());
+        assert_eq!(1, all.bonds[0].bond_id);
+        assert_eq!(other.sender.to_string(), all.bonds[0].owner);
+
+        // Closed positions drop out of the snapshot
+        env.block.height += 14400;
+        execute(deps.as_mut(), env.clone(), admin, ExecuteMsg::Redeem{ market_id: 0, stake: false, amount: None }).unwrap();
+        let res = query(deps.as_ref(), env, QueryMsg::AllBonds{ start_after: None, limit: None }).unwrap();
+        let all: AllBondsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![1], all.bonds.iter().map(|bond| bond.bond_id).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn vesting_left_by_schedule() {
+        let mut deps = mock_dependencies(&[]);
+
+        let admin = mock_info("terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8", &coins(15000000, "uusd"));
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), init_msg());
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Init {
+            control_variable: 300,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 0,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: Some(VestingCurve::Step{ interval: 10000 }),
+            wrapped_payout: None,
+            pricing: None
+        }).unwrap();
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::CreateMarket {
+            quote_denom: "uusd".to_string(),
+            control_variable: 300,
+            vesting_term: 28800,
+            minimum_price: 10000,
+            max_payout: 50,
+            fee: 1000,
+            max_debt: 1000000000000000,
+            initial_debt: 0,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: Some(20000),
+            curve: None,
+            wrapped_payout: None,
+            pricing: None
+        }).unwrap();
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Deposit{ market_id: 0, max_price: 500000 }).unwrap();
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Deposit{ market_id: 1, max_price: 500000 }).unwrap();
+        let vesting_left = |deps: Deps, env: Env| -> Vec<u64> {
+            let res = query(deps, env, QueryMsg::AllBonds{ start_after: None, limit: None }).unwrap();
+            let all: AllBondsResponse = from_binary(&res).unwrap();
+            all.bonds.iter().map(|bond| bond.vesting_left).collect()
+        };
+
+        // Halfway through, the step curve has vested one step and the cliff nothing, both are half way to maturity
+        let mut env = mock_env();
+        env.block.height += 14400;
+        assert_eq!(vec![14400, 14400], vesting_left(deps.as_ref(), env.clone()));
+
+        // Redeeming the vested steps leaves the maturity where it was
+        execute(deps.as_mut(), env.clone(), admin, ExecuteMsg::Redeem{ market_id: 0, stake: false, amount: None }).unwrap();
+        assert_eq!(vec![14400, 14400], vesting_left(deps.as_ref(), env.clone()));
+
+        env.block.height += 28800;
+        assert_eq!(vec![0, 0], vesting_left(deps.as_ref(), env));
+    }
 }
@@ -23,6 +23,54 @@ pub enum ContractError {
     
     #[error("Increment too large")]
     LargeIncrement{},
+
+    #[error("Paused")]
+    Paused{},
+
+    #[error("No pending admin")]
+    NoPendingAdmin{},
+
+    #[error("Admin proposal expired")]
+    AdminProposalExpired{},
+
+    #[error("Timelock active: propose the change instead")]
+    TimelockActive{},
+
+    #[error("Change not executable before block {executable_at}")]
+    ChangeLocked{ executable_at: u64 },
+
+    #[error("Invalid terms: {reason}")]
+    InvalidTerms{ reason: String },
+
+    #[error("Bond terms not initialized")]
+    NotInitialized{},
+
+    #[error("Market {market_id} does not exist")]
+    UnknownMarket{ market_id: u64 },
+
+    #[error("Bond terms already initialized and debt is outstanding")]
+    AlreadyInitialized{},
+
+    #[error("Market not active")]
+    MarketClosed{},
+
+    #[error("Only {vested} of the payout has vested")]
+    ExceedsVested{ vested: u64 },
+
+    #[error("No bond held in this market")]
+    NoBond{},
+
+    #[error("Nothing to redeem")]
+    NothingToRedeem{},
+
+    #[error("Early exit not enabled")]
+    EarlyExitDisabled{},
+
+    #[error("At most {limit} addresses per batch")]
+    BatchTooLarge{ limit: u32 },
+
+    #[error("Bond is not exempt from the staking warmup")]
+    NotWarmupExempt{},
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
@@ -41,7 +41,8 @@ fn transfer_ust(){
         .unwrap();
 
     let treasury_init_msg = outlet_treasury::msg::InstantiateMsg{
-        admin: addr_string.clone()
+        admin: addr_string.clone(),
+        guardian: None
     };
 
     let treasury_addr = router.instantiate_contract(
@@ -59,6 +60,11 @@ fn transfer_ust(){
         treasury: treasury_addr.clone().into(),
         dao: addr_string.clone(),
         staking: addr_string.clone(),
+        fee_destination: None,
+        guardian: None,
+        timelock: None,
+        quote_denom: None,
+        wrapped_token: None,
         total_debt: 0,
         last_decay: 0,
     };
@@ -79,7 +85,12 @@ fn transfer_ust(){
             max_payout: 50,
             fee: 1000,
             max_debt: 1000000000000000,
-            initial_debt: 0
+            initial_debt: 0,
+            fixed_expiry: None,
+            vesting_unit: None,
+            cliff: None,
+            curve: None,
+            pricing: None
 
     };
 
@@ -92,6 +103,7 @@ fn transfer_ust(){
         .unwrap();
 
     let deposit_msg = ExecuteMsg::Deposit{
+        market_id: 0,
         max_price: 50000
     };
 
@@ -2,6 +2,7 @@ pub mod contract;
 mod integration_tests;
 mod error;
 pub mod msg;
+pub mod pricing;
 pub mod state;
 
 pub use crate::error::ContractError;
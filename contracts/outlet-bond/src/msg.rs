@@ -1,12 +1,26 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use cosmwasm_std::Uint128;
+
+use cw20::Expiration;
+
+use crate::pricing::PricingModel;
+use crate::state::{FeeDestination, ParamChange, BondParameter, Terms, VestingUnit, VestingCurve};
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub admin: String,
     pub treasury: String,
     pub dao: String,
     pub staking: String,
+    pub fee_destination: Option<FeeDestination>,
+    pub guardian: Option<String>,
+    pub timelock: Option<u64>,
+    // Asset the market configured by Init buys, UST when left out
+    pub quote_denom: Option<String>,
+    // wsPHS contract, needed before a market with wrapped payouts can be set up
+    pub wrapped_token: Option<String>,
     pub total_debt: u64,
     pub last_decay: u64
 }
@@ -21,36 +35,358 @@ pub enum ExecuteMsg {
         max_payout: u64,
         fee: u64,
         max_debt: u64,
-        initial_debt: u64
+        initial_debt: u64,
+        fixed_expiry: Option<u64>,
+        vesting_unit: Option<VestingUnit>,
+        cliff: Option<u64>,
+        curve: Option<VestingCurve>,
+        wrapped_payout: Option<bool>,
+        pricing: Option<PricingModel>
+    },
+    CreateMarket{
+        quote_denom: String,
+        control_variable: u64,
+        vesting_term: u64,
+        minimum_price: u64,
+        max_payout: u64,
+        fee: u64,
+        max_debt: u64,
+        initial_debt: u64,
+        fixed_expiry: Option<u64>,
+        vesting_unit: Option<VestingUnit>,
+        cliff: Option<u64>,
+        curve: Option<VestingCurve>,
+        wrapped_payout: Option<bool>,
+        pricing: Option<PricingModel>
+    },
+    CloseMarket{
+        market_id: u64
     },
     SetStaking{
         staking: String
     },
+    SetWrappedToken{
+        address: String
+    },
+    SetBondTerms{
+        market_id: u64,
+        parameter: BondParameter,
+        value: u64
+    },
+    SetFeeDestination{
+        destination: FeeDestination
+    },
+    SetEarlyExit{
+        penalty: u64,
+        destination: FeeDestination
+    },
+    EarlyExit{
+        bond_id: u64
+    },
     Deposit {
+        market_id: u64,
         max_price: u64
     },
     SetAdjustment {
+        market_id: u64,
         addition: bool, 
         increment: u64, 
         target: u64, 
         buffer: u64 
     },
     Redeem {
+        market_id: u64,
+        stake: bool,
+        amount: Option<Uint128>
+    },
+    // Redeems what has vested in every position of the addresses, paid to the owners
+    RedeemMany {
+        addresses: Vec<String>,
         stake: bool
+    },
+    SetRedeemBatchLimit {
+        limit: u32
+    },
+    SetGuardian {
+        address: Option<String>
+    },
+    Pause {
+        deposits: bool,
+        redemptions: bool
+    },
+    Unpause {
+        deposits: bool,
+        redemptions: bool
+    },
+    AllowVestedRedemptions {
+        allowed: bool
+    },
+    ProposeNewAdmin{
+        address: String,
+        expiry: Option<Expiration>
+    },
+    AcceptAdmin{},
+    RenounceAdmin{},
+    ProposeChange{
+        change: ParamChange
+    },
+    ExecuteChange{
+        id: u64
+    },
+    CancelChange{
+        id: u64
     }
 }
 
+// Moves the listed markets and their open positions from block to time based vesting,
+// converting block counts with the average block time
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+    pub seconds_markets: Vec<u64>,
+    pub seconds_per_block: u64
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    BondInfo{address: String},
-    BondPriceInUsd{},
+    BondInfo{address: String, market_id: u64},
+    BondPriceInUsd{market_id: u64},
     MaxPayout{address: String, max_pay: u64},
-    PendingPayout{address: String}
+    PendingPayout{address: String, market_id: u64},
+    Config{},
+    Terms{market_id: u64},
+    State{market_id: u64},
+    CurrentDebt{market_id: u64},
+    DebtRatio{market_id: u64},
+    StandardizedDebtRatio{market_id: u64},
+    BondPrice{market_id: u64},
+    PercentVestedFor{address: String, market_id: u64},
+    FeesCollected{},
+    PauseStatus{},
+    Admin{},
+    PendingChanges{},
+    MarketPrice{market_id: u64},
+    CurrentCapacity{market_id: u64},
+    MarketInfo{market_id: u64},
+    Markets{start_after: Option<u64>, limit: Option<u32>},
+    EarlyExit{bond_id: u64},
+    // Fully vested positions, those vesting by height first, oldest maturity first
+    MaturedBonds{start_after: Option<MaturedCursor>, limit: Option<u32>},
+    // Every open position by id
+    AllBonds{start_after: Option<u64>, limit: Option<u32>}
 }
 
 // We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct BondInfoResponse {
+    // Id to pass to EarlyExit, none when the address holds no bond in the market
+    pub bond_id: Option<u64>,
+    pub payout: u64,
+    pub claimable: u64,
+    pub vesting: u64,
+    pub last_block: u64,
+    pub price_paid: u64,
+    pub last_time: u64,
+    pub expiry: Option<u64>,
+    pub vesting_unit: VestingUnit,
+    pub cliff_end: u64,
+    pub curve: VestingCurve,
+    pub wrapped: bool
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BondPriceInUsdResponse {
+    pub price_in_usd: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MaxPayoutResponse {
+    pub max_payout: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingPayoutResponse {
+    pub pending_payout: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub treasury: String,
+    pub dao: String,
+    pub staking: String,
+    pub fee_destination: FeeDestination,
+    pub timelock: u64,
+    pub wrapped_token: Option<String>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TermsResponse {
+    pub control_variable: u64,
+    pub vesting_term: u64,
+    pub minimum_price: u64,
+    pub max_payout: u64,
+    pub fee: u64,
+    pub max_debt: u64,
+    pub fixed_expiry: Option<u64>,
+    pub vesting_unit: VestingUnit,
+    pub cliff: u64,
+    pub curve: VestingCurve,
+    pub wrapped_payout: bool,
+    pub pricing: PricingModel
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StateResponse {
+    pub total_debt: u64,
+    pub last_decay: u64,
+    pub last_decay_time: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CurrentDebtResponse {
+    pub current_debt: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DebtRatioResponse {
+    pub debt_ratio: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StandardizedDebtRatioResponse {
+    pub debt_ratio: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BondPriceResponse {
+    pub price: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PercentVestedForResponse {
+    pub percent_vested: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeesCollectedResponse {
+    pub total: u64,
+    pub dao: u64,
+    pub burned: u64,
+    pub staking: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PauseStatusResponse {
+    pub guardian: Option<String>,
+    pub deposits_paused: bool,
+    pub redemptions_paused: bool,
+    pub allow_vested_redemptions: bool
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AdminResponse {
+    pub admin: Option<String>,
+    pub pending_admin: Option<String>,
+    pub pending_expiry: Option<Expiration>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingChangeResponse {
+    pub id: u64,
+    pub change: ParamChange,
+    pub executable_at: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingChangesResponse {
+    pub changes: Vec<PendingChangeResponse>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketPriceResponse {
+    pub price: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CurrentCapacityResponse {
+    pub capacity: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketInfoResponse {
+    pub active: bool,
+    pub start: u64,
+    pub conclusion: u64,
+    pub capacity: u64,
+    pub sold: u64,
+    pub purchased: u64,
+    pub max_payout: u64,
+    pub target_sell_rate: u64,
+    pub control_variable: u64,
+    pub current_debt: u64,
+    pub price: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketResponse {
+    pub id: u64,
+    pub quote_denom: String,
+    pub terms: Terms,
+    pub total_debt: u64,
+    pub closed: bool
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketsResponse {
+    pub markets: Vec<MarketResponse>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EarlyExitResponse {
+    pub vested: u64,
+    pub unvested: u64,
+    pub penalty: u64,
+    pub payout: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MaturedBondResponse {
+    pub bond_id: u64,
+    pub owner: String,
+    pub market_id: u64,
+    pub payout: u64
+}
+
+// Where a MaturedBonds page ended, it stays valid after the bonds on that page are redeemed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MaturedCursor {
+    pub clock: u8,
+    pub maturity: u64,
+    pub bond_id: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MaturedBondsResponse {
+    pub bonds: Vec<MaturedBondResponse>,
+    pub next_start_after: Option<MaturedCursor>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BondResponse {
+    pub bond_id: u64,
+    pub owner: String,
+    pub market_id: u64,
+    // Still vesting, what has vested but was not redeemed yet is in claimable
+    pub payout: u64,
+    pub claimable: u64,
+    // Blocks or seconds, in the position's vesting unit, until it is fully vested
+    pub vesting_left: u64,
+    pub price_paid: u64,
+    pub pending_payout: u64,
+    pub wrapped: bool
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct CountResponse {
-    pub count: i32,
+pub struct AllBondsResponse {
+    pub bonds: Vec<BondResponse>
 }
@@ -0,0 +1,315 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+// Everything a pricing model may look at when quoting a bond
+pub struct PriceInputs {
+    pub control_variable: u64,
+    pub debt_ratio: u64,
+    pub minimum_price: u64,
+    pub block_height: u64,
+    pub block_time: u64,
+    pub auction: Option<AuctionState>
+}
+
+pub trait Pricing {
+    fn price(&self, inputs: &PriceInputs) -> u64;
+
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+// Olympus style pricing: control variable times debt ratio, floored at the minimum price
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ControlVariable {}
+
+impl Pricing for ControlVariable {
+    fn price(&self, inputs: &PriceInputs) -> u64 {
+        let price = (inputs.control_variable * inputs.debt_ratio + 1000000000u64) / 10u64.pow(7);
+        if price < inputs.minimum_price {
+            return inputs.minimum_price
+        }
+        price
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FixedPrice {
+    pub price: u64
+}
+
+impl Pricing for FixedPrice {
+    fn price(&self, _inputs: &PriceInputs) -> u64 {
+        self.price
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.price == 0 {
+            return Err("fixed price must be positive".to_string())
+        }
+        Ok(())
+    }
+}
+
+// Price falls linearly from start_price to end_price over `duration` blocks
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DutchAuction {
+    pub start_price: u64,
+    pub end_price: u64,
+    pub start_block: u64,
+    pub duration: u64
+}
+
+impl Pricing for DutchAuction {
+    fn price(&self, inputs: &PriceInputs) -> u64 {
+        if inputs.block_height <= self.start_block {
+            return self.start_price
+        }
+        let elapsed = inputs.block_height - self.start_block;
+        if elapsed >= self.duration {
+            return self.end_price
+        }
+        self.start_price - (self.start_price - self.end_price) * elapsed / self.duration
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.duration == 0 {
+            return Err("auction duration must be positive".to_string())
+        }
+        if self.end_price == 0 || self.end_price > self.start_price {
+            return Err("auction must decay to a positive end price".to_string())
+        }
+        Ok(())
+    }
+}
+
+// Control variable and debt of an auction are scaled by this factor
+pub const AUCTION_SCALE: u128 = 1000000000;
+
+// Olympus Pro v2 style sequential dutch auction, times are in seconds.
+// Debt decays continuously which lowers the price, every purchase adds its payout to the debt.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SequentialDutchAuction {
+    pub capacity: u64,
+    pub initial_price: u64,
+    pub min_price: u64,
+    pub start: u64,
+    pub conclusion: u64,
+    pub deposit_interval: u64,
+    pub debt_decay_interval: u64
+}
+
+// Running state of a sequential dutch auction, amounts are in payout tokens
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuctionState {
+    pub capacity: u64,
+    pub total_debt: u64,
+    pub control_variable: u64,
+    pub last_decay: u64,
+    pub sold: u64,
+    pub purchased: u64
+}
+
+impl SequentialDutchAuction {
+    pub fn length(&self) -> u64 {
+        self.conclusion - self.start
+    }
+
+    // Capacity sold per second when the auction runs on target
+    pub fn target_sell_rate(&self) -> u64 {
+        self.capacity / self.length()
+    }
+
+    // A single deposit can buy at most one deposit interval worth of capacity
+    pub fn max_payout(&self) -> u64 {
+        (self.capacity as u128 * self.deposit_interval as u128 / self.length() as u128) as u64
+    }
+
+    fn target_debt(&self) -> u64 {
+        (self.capacity as u128 * self.debt_decay_interval as u128 / self.length() as u128) as u64
+    }
+
+    // The control variable is chosen so the auction opens at the initial price
+    pub fn open(&self) -> AuctionState {
+        let target_debt = self.target_debt();
+        AuctionState{
+            capacity: self.capacity,
+            total_debt: target_debt,
+            control_variable: (self.initial_price as u128 * AUCTION_SCALE / target_debt as u128) as u64,
+            last_decay: self.start,
+            sold: 0,
+            purchased: 0
+        }
+    }
+
+    pub fn is_active(&self, auction: &AuctionState, time: u64) -> bool {
+        time >= self.start && time < self.conclusion && auction.capacity > 0
+    }
+}
+
+impl AuctionState {
+    pub fn current_debt(&self, market: &SequentialDutchAuction, time: u64) -> u64 {
+        let elapsed = time.saturating_sub(self.last_decay) as u128;
+        let decay = self.total_debt as u128 * elapsed / market.debt_decay_interval as u128;
+        self.total_debt.saturating_sub(decay as u64)
+    }
+
+    pub fn decay(&mut self, market: &SequentialDutchAuction, time: u64) {
+        self.total_debt = self.current_debt(market, time);
+        self.last_decay = std::cmp::max(time, self.last_decay);
+    }
+
+    pub fn purchase(&mut self, payout: u64, value: u64) {
+        self.total_debt += payout;
+        self.capacity -= payout;
+        self.sold += payout;
+        self.purchased += value;
+    }
+}
+
+impl Pricing for SequentialDutchAuction {
+    fn price(&self, inputs: &PriceInputs) -> u64 {
+        let auction = match &inputs.auction {
+            Some(auction) => auction.clone(),
+            None => return self.initial_price
+        };
+        let debt = auction.current_debt(self, inputs.block_time) as u128;
+        let price = (auction.control_variable as u128 * debt / AUCTION_SCALE) as u64;
+        std::cmp::max(price, self.min_price)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.conclusion <= self.start {
+            return Err("auction must conclude after it starts".to_string())
+        }
+        if self.deposit_interval == 0 || self.deposit_interval > self.length() {
+            return Err("deposit interval must be within the auction length".to_string())
+        }
+        if self.debt_decay_interval == 0 {
+            return Err("debt decay interval must be positive".to_string())
+        }
+        if self.initial_price == 0 || self.initial_price < self.min_price {
+            return Err("initial price must be positive and above the minimum price".to_string())
+        }
+        if self.target_debt() == 0 {
+            return Err("auction capacity too small".to_string())
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PricingModel {
+    ControlVariable(ControlVariable),
+    Fixed(FixedPrice),
+    DutchAuction(DutchAuction),
+    SequentialDutchAuction(SequentialDutchAuction)
+}
+
+impl PricingModel {
+    pub fn model(&self) -> &dyn Pricing {
+        match self {
+            PricingModel::ControlVariable(model) => model,
+            PricingModel::Fixed(model) => model,
+            PricingModel::DutchAuction(model) => model,
+            PricingModel::SequentialDutchAuction(model) => model
+        }
+    }
+}
+
+impl Default for PricingModel {
+    fn default() -> Self {
+        PricingModel::ControlVariable(ControlVariable{})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inputs(block_height: u64) -> PriceInputs {
+        PriceInputs{
+            control_variable: 300000,
+            debt_ratio: 1666666,
+            minimum_price: 10000,
+            block_height,
+            block_time: 0,
+            auction: None
+        }
+    }
+
+    #[test]
+    fn control_variable() {
+        let model = PricingModel::default();
+        assert_eq!(50099, model.model().price(&inputs(0)));
+
+        let mut floored = inputs(0);
+        floored.debt_ratio = 0;
+        assert_eq!(10000, model.model().price(&floored));
+    }
+
+    #[test]
+    fn fixed() {
+        let model = PricingModel::Fixed(FixedPrice{ price: 42 });
+        assert_eq!(42, model.model().price(&inputs(0)));
+        assert!(PricingModel::Fixed(FixedPrice{ price: 0 }).model().validate().is_err());
+    }
+
+    #[test]
+    fn dutch_auction() {
+        let model = PricingModel::DutchAuction(DutchAuction{
+            start_price: 20000,
+            end_price: 10000,
+            start_block: 100,
+            duration: 1000
+        });
+        assert_eq!(20000, model.model().price(&inputs(50)));
+        assert_eq!(15000, model.model().price(&inputs(600)));
+        assert_eq!(10000, model.model().price(&inputs(1100)));
+        assert_eq!(10000, model.model().price(&inputs(5000)));
+        assert!(model.model().validate().is_ok());
+
+        let rising = PricingModel::DutchAuction(DutchAuction{
+            start_price: 10000,
+            end_price: 20000,
+            start_block: 100,
+            duration: 1000
+        });
+        assert!(rising.model().validate().is_err());
+    }
+
+    #[test]
+    fn sequential_dutch_auction() {
+        let market = SequentialDutchAuction{
+            capacity: 1000000000000,
+            initial_price: 50000,
+            min_price: 10000,
+            start: 1000,
+            conclusion: 1000 + 5 * 86400,
+            deposit_interval: 3600,
+            debt_decay_interval: 86400
+        };
+        assert!(market.validate().is_ok());
+        assert_eq!(8333333333, market.max_payout());
+
+        let mut auction = market.open();
+        let at = |time: u64, auction: &AuctionState| -> u64 {
+            let mut inputs = inputs(0);
+            inputs.block_time = time;
+            inputs.auction = Some(auction.clone());
+            market.price(&inputs)
+        };
+
+        // Opens at the initial price and decays while nobody buys
+        assert_eq!(50000, at(1000, &auction));
+        assert_eq!(25000, at(1000 + 43200, &auction));
+        assert_eq!(10000, at(1000 + 86400, &auction));
+
+        // A purchase bumps the price back up
+        auction.decay(&market, 1000 + 43200);
+        auction.purchase(market.max_payout(), 1000);
+        assert_eq!(27083, at(1000 + 43200, &auction));
+        assert_eq!(1000000000000 - 8333333333, auction.capacity);
+    }
+}
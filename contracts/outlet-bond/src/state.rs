@@ -2,19 +2,61 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_std::Addr;
-use cw_storage_plus::{Item, Map};
+use cw20::Expiration;
+
+use crate::pricing::{PricingModel, AuctionState};
+use cw_storage_plus::{Item, Map, U8Key, U64Key, IndexedMap, IndexList, Index, MultiIndex, UniqueIndex};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
     pub treasury: Addr,
     pub dao: Addr,
     pub staking: Addr,
-    pub total_debt: u64,
-    pub last_decay: u64
+    #[serde(default)]
+    pub fee_destination: FeeDestination,
+    #[serde(default)]
+    pub timelock: u64,
+    // wsPHS contract the shares of wrapped markets are wrapped into on redemption
+    #[serde(default)]
+    pub wrapped_token: Option<Addr>
 }
 
 pub const STATE: Item<State> = Item::new("state");
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeDestination {
+    Dao,
+    Burn,
+    Staking
+}
+
+// Written out rather than derived: `#[default]` on enum variants is newer than the optimizer's toolchain
+#[allow(clippy::derivable_impls)]
+impl Default for FeeDestination {
+    fn default() -> Self {
+        FeeDestination::Dao
+    }
+}
+
+// Penalty, out of 10000, taken from the unvested payout of bonds exited early
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EarlyExitTerms {
+    pub penalty: u64,
+    pub destination: FeeDestination
+}
+
+pub const EARLY_EXIT: Item<EarlyExitTerms> = Item::new("early_exit");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct FeesCollected {
+    pub dao: u64,
+    pub burned: u64,
+    pub staking: u64
+}
+
+pub const FEES_COLLECTED: Item<FeesCollected> = Item::new("fees_collected");
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Terms {
     pub control_variable: u64,
@@ -22,19 +64,150 @@ pub struct Terms {
     pub minimum_price: u64,
     pub max_payout: u64,
     pub fee: u64,
-    pub max_debt: u64
+    pub max_debt: u64,
+    // Unix time every bond of the market vests at, instead of vesting_term blocks after the deposit
+    #[serde(default)]
+    pub fixed_expiry: Option<u64>,
+    #[serde(default)]
+    pub vesting_unit: VestingUnit,
+    #[serde(default)]
+    pub cliff: u64,
+    #[serde(default)]
+    pub curve: VestingCurve,
+    // Payouts are owed in staking shares, so they keep earning rebases while they vest
+    #[serde(default)]
+    pub wrapped_payout: bool,
+    #[serde(default)]
+    pub pricing: PricingModel
 }
 
-pub const TERMS: Item<Terms> = Item::new("terms");
+// Unit of vesting_term, the adjustment buffer and debt decay in a market
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VestingUnit {
+    Blocks,
+    Seconds
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for VestingUnit {
+    fn default() -> Self {
+        VestingUnit::Blocks
+    }
+}
+
+// How a position releases its payout after the cliff, steps are counted in the market's vesting unit
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VestingCurve {
+    Linear,
+    Step{ interval: u64 }
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for VestingCurve {
+    fn default() -> Self {
+        VestingCurve::Linear
+    }
+}
+
+// Every market sells PHS for one quote asset and keeps its own debt and control variable
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Market {
+    pub quote_denom: String,
+    pub terms: Terms,
+    pub total_debt: u64,
+    pub last_decay: u64,
+    #[serde(default)]
+    pub last_decay_time: u64,
+    pub adjust: Option<Adjust>,
+    // Only present while the terms use sequential dutch auction pricing
+    pub auction: Option<AuctionState>,
+    pub closed: bool
+}
+
+pub const MARKETS: Map<U64Key, Market> = Map::new("markets");
+// Quote asset of the market Init configures, chosen at instantiation
+pub const DEFAULT_QUOTE: Item<String> = Item::new("default_quote");
+pub const MARKET_COUNT: Item<u64> = Item::new("market_count");
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Bond {
+    pub owner: Addr,
+    pub market_id: u64,
     pub payout: u64,
+    // Vested payout left unclaimed by a partial redemption, no longer on the schedule
+    #[serde(default)]
+    pub claimable: u64,
     pub vesting: u64,
     pub last_block: u64,
-    pub price_paid: u64
+    pub price_paid: u64,
+    #[serde(default)]
+    pub last_time: u64,
+    #[serde(default)]
+    pub expiry: Option<u64>,
+    #[serde(default)]
+    pub vesting_unit: VestingUnit,
+    // Block height or time, on the clock the position vests with, before which nothing vests
+    #[serde(default)]
+    pub cliff_end: u64,
+    #[serde(default)]
+    pub curve: VestingCurve,
+    // payout and claimable are staking shares rather than PHS
+    #[serde(default)]
+    pub wrapped: bool
+}
+
+// Clocks in the maturity index, height-vesting positions sort first
+pub const MATURES_AT_HEIGHT: u8 = 0;
+pub const MATURES_AT_TIME: u8 = 1;
+
+impl Bond {
+    // Clock and height or time the position is fully vested at, the clock vesting_progress uses for it
+    pub fn maturity(&self) -> (u8, u64) {
+        match (self.expiry, &self.vesting_unit) {
+            (Some(expiry), _) => (MATURES_AT_TIME, expiry),
+            (None, VestingUnit::Blocks) => (MATURES_AT_HEIGHT, self.last_block + self.vesting),
+            (None, VestingUnit::Seconds) => (MATURES_AT_TIME, self.last_time + self.vesting)
+        }
+    }
+}
+
+pub struct BondIndexes<'a> {
+    // Depositors hold one position per market, topped up on every deposit
+    pub owner: UniqueIndex<'a, (Addr, U64Key), Bond>,
+    pub maturity: MultiIndex<'a, (U8Key, U64Key, Vec<u8>), Bond>
 }
-pub const BOND: Map<&Addr, Bond> = Map::new("bond");
+
+impl<'a> IndexList<Bond> for BondIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Bond>> + '_> {
+        let v: Vec<&dyn Index<Bond>> = vec![&self.owner, &self.maturity];
+        Box::new(v.into_iter())
+    }
+}
+
+// Positions by id, the indexes find them by owner and market or by when they are fully vested
+pub fn bonds<'a>() -> IndexedMap<'a, U64Key, Bond, BondIndexes<'a>> {
+    let indexes = BondIndexes{
+        owner: UniqueIndex::new(
+            |bond| (bond.owner.clone(), U64Key::new(bond.market_id)),
+            "bonds__owner"
+        ),
+        maturity: MultiIndex::new(
+            |bond, pk| {
+                let (clock, maturity) = bond.maturity();
+                (U8Key::new(clock), U64Key::new(maturity), pk)
+            },
+            "bonds",
+            "bonds__maturity"
+        )
+    };
+    IndexedMap::new("bonds", indexes)
+}
+
+pub const BOND_COUNT: Item<u64> = Item::new("bond_count");
+// Most addresses a single RedeemMany may go through
+pub const REDEEM_BATCH_LIMIT: Item<u32> = Item::new("redeem_batch_limit");
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Adjust {
@@ -42,7 +215,105 @@ pub struct Adjust {
     pub rate: u64,
     pub target: u64,
     pub buffer: u64,
-    pub last_block: u64
+    pub last_block: u64,
+    #[serde(default)]
+    pub last_time: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PauseStatus {
+    pub deposits: bool,
+    pub redemptions: bool,
+    pub allow_vested_redemptions: bool
+}
+
+pub const PAUSE_STATUS: Item<PauseStatus> = Item::new("pause_status");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingAdmin {
+    pub address: Addr,
+    pub expiry: Option<Expiration>
+}
+
+pub const PENDING_ADMIN: Item<PendingAdmin> = Item::new("pending_admin");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BondParameter {
+    Vesting,
+    MaxPayout,
+    Fee,
+    Debt,
+    MinimumPrice
+}
+
+// Parameter changes that have to sit in the queue for `State.timelock` blocks
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamChange {
+    Terms {
+        market_id: u64,
+        terms: Terms
+    },
+    BondTerm {
+        market_id: u64,
+        parameter: BondParameter,
+        value: u64
+    },
+    Adjustment {
+        market_id: u64,
+        addition: bool,
+        increment: u64,
+        target: u64,
+        buffer: u64
+    },
+    Staking {
+        staking: String
+    },
+    WrappedToken {
+        address: String
+    },
+    Timelock {
+        blocks: u64
+    },
+    EarlyExit {
+        penalty: u64,
+        destination: FeeDestination
+    },
+    FeeDestination {
+        destination: FeeDestination
+    },
+    RedeemBatchLimit {
+        limit: u32
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingChange {
+    pub change: ParamChange,
+    pub executable_at: u64
+}
+
+pub const PENDING_CHANGES: Map<U64Key, PendingChange> = Map::new("pending_changes");
+pub const CHANGE_COUNT: Item<u64> = Item::new("change_count");
+
+// Layout of the single market contract, only read by migrate to move it into market 0.
+// Its state item shares the key of STATE and carries the debt that now lives on the market.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyDebt {
+    pub total_debt: u64,
+    pub last_decay: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyBond {
+    pub payout: u64,
+    pub vesting: u64,
+    pub last_block: u64,
+    pub price_paid: u64
 }
 
-pub const ADJUST: Item<Adjust> = Item::new("adjust");
\ No newline at end of file
+pub const LEGACY_DEBT: Item<LegacyDebt> = Item::new("state");
+pub const LEGACY_TERMS: Item<Terms> = Item::new("terms");
+pub const LEGACY_ADJUST: Item<Adjust> = Item::new("adjust");
+pub const LEGACY_BOND: Map<&Addr, LegacyBond> = Map::new("bond");
@@ -0,0 +1,29 @@
+use std::env::current_dir;
+use std::fs::create_dir_all;
+
+use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
+
+use outlet_staking::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, ConfigResponse, EpochResponse, IndexResponse,
+                        StakedBalanceResponse, WarmupInfoResponse, WarmupExemptResponse, SharesForResponse,
+                        LockBoostsResponse, LocksResponse, CooldownResponse};
+
+fn main() {
+    let mut out_dir = current_dir().unwrap();
+    out_dir.push("schema");
+    create_dir_all(&out_dir).unwrap();
+    remove_schemas(&out_dir).unwrap();
+
+    export_schema(&schema_for!(InstantiateMsg), &out_dir);
+    export_schema(&schema_for!(ExecuteMsg), &out_dir);
+    export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(EpochResponse), &out_dir);
+    export_schema(&schema_for!(IndexResponse), &out_dir);
+    export_schema(&schema_for!(StakedBalanceResponse), &out_dir);
+    export_schema(&schema_for!(WarmupInfoResponse), &out_dir);
+    export_schema(&schema_for!(WarmupExemptResponse), &out_dir);
+    export_schema(&schema_for!(SharesForResponse), &out_dir);
+    export_schema(&schema_for!(LockBoostsResponse), &out_dir);
+    export_schema(&schema_for!(LocksResponse), &out_dir);
+    export_schema(&schema_for!(CooldownResponse), &out_dir);
+}
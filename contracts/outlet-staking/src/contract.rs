@@ -0,0 +1,876 @@
+use std::convert::TryInto;
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, StdError, CosmosMsg,
+                    WasmMsg, Addr, Uint128, Storage, Order};
+use cw2::set_contract_version;
+
+use crate::error::ContractError;
+use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, ConfigResponse, EpochResponse, IndexResponse,
+                StakedBalanceResponse, WarmupInfoResponse, WarmupExemptResponse, SharesForResponse, ReceiveSharesMsg,
+                LockBoostsResponse, LockResponse, LocksResponse, CooldownResponse};
+use crate::state::{Config, CONFIG, Epoch, EPOCH, Pool, POOL, SHARES, WarmupClaim, WARMUP, WARMUP_EXEMPT, LockBoost,
+                    LOCK_BOOSTS, Lock, LOCKS, LOCK_COUNT, Cooldown, COOLDOWNS};
+
+use cw_storage_plus::U64Key;
+
+use cw_controllers::Admin;
+use outlet_treasury::msg::{ExecuteMsg as TreasuryExecuteMsg};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:outlet-staking";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+static STAKING_ADMIN: &Admin = &Admin::new("staking_admin");
+
+// The index is the PHS value of one share scaled by this, it starts at 1
+pub const INDEX_PRECISION: u128 = 1_000_000_000;
+// Lock boosts are in hundredths of a percent, 10000 leaves rewards as they are
+pub const BOOST_PRECISION: u64 = 10000;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    mut deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let admin_addr = deps.api.addr_validate(&msg.admin)?;
+    STAKING_ADMIN.set(deps.branch(), Some(admin_addr))?;
+
+    let config = Config{
+        phs: deps.api.addr_validate(&msg.phs)?,
+        epoch_length: msg.epoch_length,
+        warmup_period: msg.warmup_period,
+        cooldown_period: 0
+    };
+    CONFIG.save(deps.storage, &config)?;
+    EPOCH.save(deps.storage, &Epoch{ number: 0, end_block: msg.first_epoch_block })?;
+    POOL.save(deps.storage, &Pool::default())?;
+
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("owner", info.sender))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Stake {recipient, amount} => stake(deps, env, info, recipient, amount),
+        ExecuteMsg::ClaimWarmup {recipient} => claim_warmup(deps, env, recipient),
+        ExecuteMsg::Unstake {amount} => unstake(deps, env, info, amount),
+        ExecuteMsg::ClaimCooldown {} => claim_cooldown(deps, env, info),
+        ExecuteMsg::Lock {shares, epochs} => lock(deps, env, info, shares, epochs),
+        ExecuteMsg::Unlock {lock_id} => unlock(deps, env, info, lock_id),
+        ExecuteMsg::TransferShares {recipient, shares} => transfer_shares(deps, info, recipient, shares),
+        ExecuteMsg::SendShares {contract, shares, msg} => send_shares(deps, info, contract, shares, msg),
+        ExecuteMsg::AddRewards {amount} => add_rewards(deps, env, info, amount),
+        ExecuteMsg::Rebase {} => {
+            rebase(deps.storage, &env)?;
+            Ok(Response::new().add_attribute("method", "rebase"))
+        },
+        ExecuteMsg::SetWarmupPeriod {period} => set_warmup_period(deps, info, period),
+        ExecuteMsg::SetWarmupExempt {address, exempt} => set_warmup_exempt(deps, info, address, exempt),
+        ExecuteMsg::SetCooldownPeriod {period} => set_cooldown_period(deps, info, period),
+        ExecuteMsg::SetLockBoosts {boosts} => set_lock_boosts(deps, info, boosts)
+    }
+}
+
+// Stakes go through warmup when a warmup period is set, otherwise they count as staked right away
+pub fn stake(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    amount: Uint128
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount{})
+    }
+    let recipient = deps.api.addr_validate(&recipient)?;
+    let epoch = rebase(deps.storage, &env)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    let mut pool = POOL.load(deps.storage)?;
+    let shares = to_shares(&pool, amount);
+    pool.staked += amount;
+    pool.shares += shares;
+    POOL.save(deps.storage, &pool)?;
+
+    let warmup = config.warmup_period > 0 &&
+        !WARMUP_EXEMPT.may_load(deps.storage, &recipient)?.unwrap_or(false);
+    if !warmup {
+        add_shares(deps.storage, &recipient, shares)?;
+    } else {
+        // Topping up restarts the warmup for the whole claim
+        let claim = WARMUP.may_load(deps.storage, &recipient)?;
+        let claim = WarmupClaim{
+            deposit: claim.as_ref().map_or(Uint128::zero(), |claim| claim.deposit) + amount,
+            shares: claim.as_ref().map_or(Uint128::zero(), |claim| claim.shares) + shares,
+            expiry: epoch.number + config.warmup_period
+        };
+        WARMUP.save(deps.storage, &recipient, &claim)?;
+    }
+
+    Ok(Response::new()
+        .add_message(phs_message(&config, TreasuryExecuteMsg::TransferFrom{
+            owner: info.sender.to_string(),
+            recipient: env.contract.address.to_string(),
+            amount
+        })?)
+        .add_attribute("method", "stake")
+        .add_attribute("recipient", recipient)
+        .add_attribute("amount", amount)
+        .add_attribute("shares", shares)
+        .add_attribute("warmup", warmup.to_string()))
+}
+
+// Anyone can move a finished warmup into the recipient's stake
+pub fn claim_warmup(deps: DepsMut, env: Env, recipient: String) -> Result<Response, ContractError> {
+    let recipient = deps.api.addr_validate(&recipient)?;
+    let epoch = rebase(deps.storage, &env)?;
+    let claim = WARMUP.may_load(deps.storage, &recipient)?.ok_or(ContractError::NoWarmup{})?;
+    if epoch.number < claim.expiry {
+        return Err(ContractError::WarmupActive{ expiry: claim.expiry })
+    }
+    WARMUP.remove(deps.storage, &recipient);
+    add_shares(deps.storage, &recipient, claim.shares)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "claim_warmup")
+        .add_attribute("recipient", recipient)
+        .add_attribute("shares", claim.shares))
+}
+
+pub fn unstake(deps: DepsMut, env: Env, info: MessageInfo, amount: Uint128) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount{})
+    }
+    let epoch = rebase(deps.storage, &env)?;
+    let mut pool = POOL.load(deps.storage)?;
+    let held = SHARES.may_load(deps.storage, &info.sender)?.unwrap_or_default();
+    // Round the shares up so unstaking never takes value from the other stakers
+    let mut shares = to_shares(&pool, amount);
+    if to_amount(&pool, shares) < amount {
+        shares += Uint128::new(1);
+    }
+    if shares > held {
+        return Err(ContractError::InsufficientStake{})
+    }
+    SHARES.save(deps.storage, &info.sender, &(held - shares))?;
+    pool.staked -= amount;
+    pool.shares -= shares;
+    POOL.save(deps.storage, &pool)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    if config.cooldown_period > 0 {
+        // Unstaking more restarts the cooldown for the whole amount
+        let cooldown = COOLDOWNS.may_load(deps.storage, &info.sender)?;
+        let cooldown = Cooldown{
+            amount: cooldown.map_or(Uint128::zero(), |cooldown| cooldown.amount) + amount,
+            expiry: epoch.number + config.cooldown_period
+        };
+        COOLDOWNS.save(deps.storage, &info.sender, &cooldown)?;
+        return Ok(Response::new()
+            .add_attribute("method", "unstake")
+            .add_attribute("amount", amount)
+            .add_attribute("cooldown_expiry", cooldown.expiry.to_string()))
+    }
+    Ok(Response::new()
+        .add_message(phs_message(&config, TreasuryExecuteMsg::Transfer{
+            recipient: info.sender.to_string(),
+            amount
+        })?)
+        .add_attribute("method", "unstake")
+        .add_attribute("amount", amount))
+}
+
+pub fn claim_cooldown(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let epoch = rebase(deps.storage, &env)?;
+    let cooldown = COOLDOWNS.may_load(deps.storage, &info.sender)?.ok_or(ContractError::NoCooldown{})?;
+    if epoch.number < cooldown.expiry {
+        return Err(ContractError::CooldownActive{ expiry: cooldown.expiry })
+    }
+    COOLDOWNS.remove(deps.storage, &info.sender);
+
+    let config = CONFIG.load(deps.storage)?;
+    Ok(Response::new()
+        .add_message(phs_message(&config, TreasuryExecuteMsg::Transfer{
+            recipient: info.sender.to_string(),
+            amount: cooldown.amount
+        })?)
+        .add_attribute("method", "claim_cooldown")
+        .add_attribute("amount", cooldown.amount))
+}
+
+pub fn lock(deps: DepsMut, env: Env, info: MessageInfo, shares: Uint128, epochs: u64) -> Result<Response, ContractError> {
+    if shares.is_zero() {
+        return Err(ContractError::InvalidZeroAmount{})
+    }
+    let boost = lock_boost(deps.storage, epochs)?;
+    let epoch = rebase(deps.storage, &env)?;
+    let held = SHARES.may_load(deps.storage, &info.sender)?.unwrap_or_default();
+    if shares > held {
+        return Err(ContractError::InsufficientStake{})
+    }
+    SHARES.save(deps.storage, &info.sender, &(held - shares))?;
+
+    let mut pool = POOL.load(deps.storage)?;
+    let weight = shares.multiply_ratio(boost - BOOST_PRECISION, BOOST_PRECISION);
+    pool.boost_weight += weight;
+    POOL.save(deps.storage, &pool)?;
+
+    let id = LOCK_COUNT.may_load(deps.storage)?.unwrap_or(0);
+    LOCK_COUNT.save(deps.storage, &(id + 1))?;
+    let lock = Lock{
+        shares,
+        boost,
+        weight,
+        unlock_epoch: epoch.number + epochs,
+        index_start: pool.boost_index
+    };
+    LOCKS.save(deps.storage, (&info.sender, U64Key::new(id)), &lock)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "lock")
+        .add_attribute("lock_id", id.to_string())
+        .add_attribute("shares", shares)
+        .add_attribute("boost", boost.to_string())
+        .add_attribute("unlock_epoch", lock.unlock_epoch.to_string()))
+}
+
+pub fn unlock(deps: DepsMut, env: Env, info: MessageInfo, lock_id: u64) -> Result<Response, ContractError> {
+    let epoch = rebase(deps.storage, &env)?;
+    let lock = LOCKS.may_load(deps.storage, (&info.sender, U64Key::new(lock_id)))?.ok_or(ContractError::NoLock{})?;
+    if epoch.number < lock.unlock_epoch {
+        return Err(ContractError::LockActive{ unlock_epoch: lock.unlock_epoch })
+    }
+    LOCKS.remove(deps.storage, (&info.sender, U64Key::new(lock_id)));
+
+    // Boost rewards are staked, so they keep earning like the rest of the stake
+    let mut pool = POOL.load(deps.storage)?;
+    let rewards = boost_rewards(&pool, &lock);
+    pool.boost_weight -= lock.weight;
+    pool.boosted -= rewards;
+    let reward_shares = to_shares(&pool, rewards);
+    pool.staked += rewards;
+    pool.shares += reward_shares;
+    POOL.save(deps.storage, &pool)?;
+    add_shares(deps.storage, &info.sender, lock.shares + reward_shares)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "unlock")
+        .add_attribute("lock_id", lock_id.to_string())
+        .add_attribute("shares", lock.shares)
+        .add_attribute("rewards", rewards))
+}
+
+// The best boost among the tiers the lockup qualifies for
+fn lock_boost(storage: &dyn Storage, epochs: u64) -> Result<u64, ContractError> {
+    LOCK_BOOSTS.may_load(storage)?.unwrap_or_default()
+        .iter()
+        .filter(|tier| tier.epochs <= epochs)
+        .map(|tier| tier.boost)
+        .max()
+        .ok_or(ContractError::InvalidLockup{})
+}
+
+fn boost_rewards(pool: &Pool, lock: &Lock) -> Uint128 {
+    let rewards = lock.weight.multiply_ratio(pool.boost_index - lock.index_start, INDEX_PRECISION);
+    // Rounding must not hand out more than was set aside
+    rewards.min(pool.boosted)
+}
+
+pub fn transfer_shares(deps: DepsMut, info: MessageInfo, recipient: String, shares: Uint128) -> Result<Response, ContractError> {
+    let recipient = deps.api.addr_validate(&recipient)?;
+    move_shares(deps.storage, &info.sender, &recipient, shares)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "transfer_shares")
+        .add_attribute("recipient", recipient)
+        .add_attribute("shares", shares))
+}
+
+pub fn send_shares(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: String,
+    shares: Uint128,
+    msg: Binary
+) -> Result<Response, ContractError> {
+    let contract = deps.api.addr_validate(&contract)?;
+    move_shares(deps.storage, &info.sender, &contract, shares)?;
+
+    let receive = ReceiveSharesMsg{
+        sender: info.sender.to_string(),
+        shares,
+        msg
+    };
+    Ok(Response::new()
+        .add_message(receive.into_cosmos_msg(&contract)?)
+        .add_attribute("method", "send_shares")
+        .add_attribute("contract", contract)
+        .add_attribute("shares", shares))
+}
+
+fn move_shares(storage: &mut dyn Storage, from: &Addr, to: &Addr, shares: Uint128) -> Result<(), ContractError> {
+    if shares.is_zero() {
+        return Err(ContractError::InvalidZeroAmount{})
+    }
+    let held = SHARES.may_load(storage, from)?.unwrap_or_default();
+    if shares > held {
+        return Err(ContractError::InsufficientStake{})
+    }
+    SHARES.save(storage, from, &(held - shares))?;
+    add_shares(storage, to, shares)?;
+    Ok(())
+}
+
+pub fn add_rewards(deps: DepsMut, env: Env, info: MessageInfo, amount: Uint128) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount{})
+    }
+    let mut pool = POOL.load(deps.storage)?;
+    pool.pending_rewards += amount;
+    POOL.save(deps.storage, &pool)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    Ok(Response::new()
+        .add_message(phs_message(&config, TreasuryExecuteMsg::TransferFrom{
+            owner: info.sender.to_string(),
+            recipient: env.contract.address.to_string(),
+            amount
+        })?)
+        .add_attribute("method", "add_rewards")
+        .add_attribute("amount", amount))
+}
+
+// Ends every epoch that has passed and hands the pending rewards to current stakers
+fn rebase(storage: &mut dyn Storage, env: &Env) -> StdResult<Epoch> {
+    let (epoch, pool) = rebased(storage, env)?;
+    EPOCH.save(storage, &epoch)?;
+    POOL.save(storage, &pool)?;
+    Ok(epoch)
+}
+
+// Epoch and pool as they stand once a rebase at the current height has run
+fn rebased(storage: &dyn Storage, env: &Env) -> StdResult<(Epoch, Pool)> {
+    let mut epoch = EPOCH.load(storage)?;
+    let mut pool = POOL.load(storage)?;
+    if env.block.height < epoch.end_block {
+        return Ok((epoch, pool))
+    }
+    let config = CONFIG.load(storage)?;
+    let elapsed = (env.block.height - epoch.end_block) / config.epoch_length.max(1) + 1;
+    epoch.number += elapsed;
+    epoch.end_block += elapsed * config.epoch_length;
+
+    // Locks earn their boost on top of the rewards their shares get through the index
+    if !pool.shares.is_zero() {
+        let rewards = pool.pending_rewards;
+        let base = rewards.multiply_ratio(pool.shares, pool.shares + pool.boost_weight);
+        if !pool.boost_weight.is_zero() {
+            pool.boost_index += (rewards - base).multiply_ratio(INDEX_PRECISION, pool.boost_weight);
+            pool.boosted += rewards - base;
+        }
+        pool.staked += base;
+        pool.pending_rewards = Uint128::zero();
+    }
+    Ok((epoch, pool))
+}
+
+fn add_shares(storage: &mut dyn Storage, owner: &Addr, shares: Uint128) -> StdResult<Uint128> {
+    SHARES.update(storage, owner, |held| -> StdResult<_> {
+        Ok(held.unwrap_or_default() + shares)
+    })
+}
+
+fn to_shares(pool: &Pool, amount: Uint128) -> Uint128 {
+    if pool.shares.is_zero() || pool.staked.is_zero() {
+        return amount
+    }
+    amount.multiply_ratio(pool.shares, pool.staked)
+}
+
+fn to_amount(pool: &Pool, shares: Uint128) -> Uint128 {
+    if pool.shares.is_zero() {
+        return Uint128::zero()
+    }
+    shares.multiply_ratio(pool.staked, pool.shares)
+}
+
+fn phs_message(config: &Config, msg: TreasuryExecuteMsg) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute{
+        funds: vec![],
+        contract_addr: config.phs.to_string(),
+        msg: to_binary(&msg)?
+    }))
+}
+
+pub fn set_warmup_period(mut deps: DepsMut, info: MessageInfo, period: u64) -> Result<Response, ContractError> {
+    only_admin(deps.branch(), info)?;
+    CONFIG.update(deps.storage, |mut config| -> StdResult<_>{
+        config.warmup_period = period;
+        Ok(config)
+    })?;
+    Ok(Response::new()
+        .add_attribute("method", "set_warmup_period")
+        .add_attribute("period", period.to_string()))
+}
+
+pub fn set_warmup_exempt(mut deps: DepsMut, info: MessageInfo, address: String, exempt: bool) -> Result<Response, ContractError> {
+    only_admin(deps.branch(), info)?;
+    let address = deps.api.addr_validate(&address)?;
+    if exempt {
+        WARMUP_EXEMPT.save(deps.storage, &address, &true)?;
+    } else {
+        WARMUP_EXEMPT.remove(deps.storage, &address);
+    }
+    Ok(Response::new()
+        .add_attribute("method", "set_warmup_exempt")
+        .add_attribute("address", address)
+        .add_attribute("exempt", exempt.to_string()))
+}
+
+pub fn set_cooldown_period(mut deps: DepsMut, info: MessageInfo, period: u64) -> Result<Response, ContractError> {
+    only_admin(deps.branch(), info)?;
+    CONFIG.update(deps.storage, |mut config| -> StdResult<_>{
+        config.cooldown_period = period;
+        Ok(config)
+    })?;
+    Ok(Response::new()
+        .add_attribute("method", "set_cooldown_period")
+        .add_attribute("period", period.to_string()))
+}
+
+pub fn set_lock_boosts(mut deps: DepsMut, info: MessageInfo, mut boosts: Vec<LockBoost>) -> Result<Response, ContractError> {
+    only_admin(deps.branch(), info)?;
+    if boosts.iter().any(|tier| tier.epochs == 0 || tier.boost < BOOST_PRECISION) {
+        return Err(ContractError::InvalidBoost{})
+    }
+    boosts.sort_by_key(|tier| tier.epochs);
+    LOCK_BOOSTS.save(deps.storage, &boosts)?;
+    Ok(Response::new().add_attribute("method", "set_lock_boosts"))
+}
+
+fn only_admin(deps: DepsMut, info: MessageInfo) -> Result< (),ContractError>{
+    STAKING_ADMIN.assert_admin(deps.as_ref(), &info.sender)
+        .map_err(|_not_admin| ContractError::Unauthorized{})
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config{} => to_binary(&query_config(deps)?),
+        QueryMsg::Epoch{} => to_binary(&query_epoch(deps)?),
+        QueryMsg::Index{} => to_binary(&query_index(deps)?),
+        QueryMsg::StakedBalance{address} => to_binary(&query_staked_balance(deps, address)?),
+        QueryMsg::WarmupInfo{address} => to_binary(&query_warmup_info(deps, env, address)?),
+        QueryMsg::WarmupExempt{address} => to_binary(&query_warmup_exempt(deps, address)?),
+        QueryMsg::SharesFor{amount} => to_binary(&query_shares_for(deps, env, amount)?),
+        QueryMsg::LockBoosts{} => to_binary(&query_lock_boosts(deps)?),
+        QueryMsg::Locks{address} => to_binary(&query_locks(deps, env, address)?),
+        QueryMsg::Cooldown{address} => to_binary(&query_cooldown(deps, env, address)?)
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let admin = STAKING_ADMIN.get(deps)?;
+    Ok(ConfigResponse{
+        admin: admin.map(String::from),
+        phs: config.phs.to_string(),
+        epoch_length: config.epoch_length,
+        warmup_period: config.warmup_period,
+        cooldown_period: config.cooldown_period
+    })
+}
+
+fn query_epoch(deps: Deps) -> StdResult<EpochResponse> {
+    let epoch = EPOCH.load(deps.storage)?;
+    Ok(EpochResponse{
+        number: epoch.number,
+        end_block: epoch.end_block
+    })
+}
+
+fn query_index(deps: Deps) -> StdResult<IndexResponse> {
+    let pool = POOL.load(deps.storage)?;
+    let index = match pool.shares.is_zero() {
+        true => Uint128::new(INDEX_PRECISION),
+        false => pool.staked.multiply_ratio(INDEX_PRECISION, pool.shares)
+    };
+    Ok(IndexResponse{ index })
+}
+
+fn query_staked_balance(deps: Deps, address: String) -> StdResult<StakedBalanceResponse> {
+    let owner = deps.api.addr_validate(&address)?;
+    let shares = SHARES.may_load(deps.storage, &owner)?.unwrap_or_default();
+    let pool = POOL.load(deps.storage)?;
+    Ok(StakedBalanceResponse{
+        shares,
+        balance: to_amount(&pool, shares)
+    })
+}
+
+// Matches what staking `amount` in this block would mint, including a rebase that is due
+fn query_shares_for(deps: Deps, env: Env, amount: Uint128) -> StdResult<SharesForResponse> {
+    let (_, pool) = rebased(deps.storage, &env)?;
+    Ok(SharesForResponse{ shares: to_shares(&pool, amount) })
+}
+
+// Expiry is an epoch number, a claim is ready once that epoch has started
+fn query_warmup_info(deps: Deps, env: Env, address: String) -> StdResult<WarmupInfoResponse> {
+    let owner = deps.api.addr_validate(&address)?;
+    let claim = match WARMUP.may_load(deps.storage, &owner)? {
+        Some(claim) => claim,
+        None => return Ok(WarmupInfoResponse::default())
+    };
+    let pool = POOL.load(deps.storage)?;
+    let epoch = EPOCH.load(deps.storage)?;
+    let current = match env.block.height < epoch.end_block {
+        true => epoch.number,
+        false => epoch.number + 1
+    };
+    Ok(WarmupInfoResponse{
+        deposit: claim.deposit,
+        shares: claim.shares,
+        balance: to_amount(&pool, claim.shares),
+        expiry: claim.expiry,
+        claimable: current >= claim.expiry
+    })
+}
+
+fn query_warmup_exempt(deps: Deps, address: String) -> StdResult<WarmupExemptResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    Ok(WarmupExemptResponse{ exempt: WARMUP_EXEMPT.may_load(deps.storage, &address)?.unwrap_or(false) })
+}
+
+fn query_lock_boosts(deps: Deps) -> StdResult<LockBoostsResponse> {
+    Ok(LockBoostsResponse{ boosts: LOCK_BOOSTS.may_load(deps.storage)?.unwrap_or_default() })
+}
+
+// Balances and pending boost rewards include a rebase that is due
+fn query_locks(deps: Deps, env: Env, address: String) -> StdResult<LocksResponse> {
+    let owner = deps.api.addr_validate(&address)?;
+    let (_, pool) = rebased(deps.storage, &env)?;
+    let locks = LOCKS
+        .prefix(&owner)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (key, lock) = item?;
+            let id = key.as_slice().try_into().map_err(|_| StdError::generic_err("invalid lock id"))?;
+            Ok(LockResponse{
+                id: u64::from_be_bytes(id),
+                shares: lock.shares,
+                balance: to_amount(&pool, lock.shares),
+                boost: lock.boost,
+                unlock_epoch: lock.unlock_epoch,
+                pending_rewards: boost_rewards(&pool, &lock)
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(LocksResponse{ locks })
+}
+
+fn query_cooldown(deps: Deps, env: Env, address: String) -> StdResult<CooldownResponse> {
+    let owner = deps.api.addr_validate(&address)?;
+    let cooldown = match COOLDOWNS.may_load(deps.storage, &owner)? {
+        Some(cooldown) => cooldown,
+        None => return Ok(CooldownResponse::default())
+    };
+    let (epoch, _) = rebased(deps.storage, &env)?;
+    Ok(CooldownResponse{
+        amount: cooldown.amount,
+        expiry: cooldown.expiry,
+        claimable: epoch.number >= cooldown.expiry
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::from_binary;
+
+    const ADMIN: &str = "terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8";
+    const PHS: &str = "terra1e8ryd9ezefuucd4mje33zdms9m2s90m57878v9";
+    const BOND: &str = "terra1wjz4hmnrdm4ycn8mnrcjq84j4z9cxe5mmysh53";
+    const STAKER: &str = "terra1q0fdx7yu5f6ddx5kckh4m5w4r9ytxvm0jrzfz8";
+
+    fn init_msg(warmup_period: u64) -> InstantiateMsg {
+        InstantiateMsg {
+            admin: ADMIN.to_string(),
+            phs: PHS.to_string(),
+            epoch_length: 100,
+            first_epoch_block: mock_env().block.height + 100,
+            warmup_period
+        }
+    }
+
+    fn staked_balance(deps: Deps, address: &str) -> StakedBalanceResponse {
+        let res = query(deps, mock_env(), QueryMsg::StakedBalance{ address: address.to_string() }).unwrap();
+        from_binary(&res).unwrap()
+    }
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = mock_dependencies(&[]);
+        let res = instantiate(deps.as_mut(), mock_env(), mock_info(ADMIN, &[]), init_msg(2)).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Config{}).unwrap();
+        let config: ConfigResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(ADMIN.to_string()), config.admin);
+        assert_eq!(2, config.warmup_period);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Index{}).unwrap();
+        let index: IndexResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128::new(INDEX_PRECISION), index.index);
+    }
+
+    #[test]
+    fn stake_without_warmup() {
+        let mut deps = mock_dependencies(&[]);
+        instantiate(deps.as_mut(), mock_env(), mock_info(ADMIN, &[]), init_msg(0)).unwrap();
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info(BOND, &[]), ExecuteMsg::Stake{
+            recipient: STAKER.to_string(), amount: Uint128::new(1000) }).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+            funds: vec![],
+            contract_addr: PHS.to_string(),
+            msg: to_binary(&TreasuryExecuteMsg::TransferFrom{
+                owner: BOND.to_string(),
+                recipient: mock_env().contract.address.to_string(),
+                amount: Uint128::new(1000)
+            }).unwrap()
+        }));
+        assert_eq!(Uint128::new(1000), staked_balance(deps.as_ref(), STAKER).balance);
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info(STAKER, &[]), ExecuteMsg::ClaimWarmup{
+            recipient: STAKER.to_string() });
+        match res {
+            Err(ContractError::NoWarmup{}) => {},
+            _ => panic!("Must return no warmup error")
+        }
+    }
+
+    #[test]
+    fn warmup_claim() {
+        let mut deps = mock_dependencies(&[]);
+        instantiate(deps.as_mut(), mock_env(), mock_info(ADMIN, &[]), init_msg(2)).unwrap();
+
+        execute(deps.as_mut(), mock_env(), mock_info(BOND, &[]), ExecuteMsg::Stake{
+            recipient: STAKER.to_string(), amount: Uint128::new(1000) }).unwrap();
+        assert!(staked_balance(deps.as_ref(), STAKER).shares.is_zero());
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::WarmupInfo{ address: STAKER.to_string() }).unwrap();
+        let warmup: WarmupInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(WarmupInfoResponse{
+            deposit: Uint128::new(1000),
+            shares: Uint128::new(1000),
+            balance: Uint128::new(1000),
+            expiry: 2,
+            claimable: false
+        }, warmup);
+
+        // One epoch in, the warmup still has an epoch to go
+        let mut env = mock_env();
+        env.block.height += 100;
+        let res = execute(deps.as_mut(), env.clone(), mock_info(STAKER, &[]), ExecuteMsg::ClaimWarmup{
+            recipient: STAKER.to_string() });
+        match res {
+            Err(ContractError::WarmupActive{ expiry: 2 }) => {},
+            _ => panic!("Must return warmup active error")
+        }
+
+        env.block.height += 100;
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::WarmupInfo{ address: STAKER.to_string() }).unwrap();
+        let warmup: WarmupInfoResponse = from_binary(&res).unwrap();
+        assert!(warmup.claimable);
+        execute(deps.as_mut(), env, mock_info(ADMIN, &[]), ExecuteMsg::ClaimWarmup{
+            recipient: STAKER.to_string() }).unwrap();
+        assert_eq!(Uint128::new(1000), staked_balance(deps.as_ref(), STAKER).balance);
+    }
+
+    #[test]
+    fn rewards_and_unstake() {
+        let mut deps = mock_dependencies(&[]);
+        instantiate(deps.as_mut(), mock_env(), mock_info(ADMIN, &[]), init_msg(0)).unwrap();
+
+        execute(deps.as_mut(), mock_env(), mock_info(BOND, &[]), ExecuteMsg::Stake{
+            recipient: STAKER.to_string(), amount: Uint128::new(1000) }).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info(BOND, &[]), ExecuteMsg::Stake{
+            recipient: BOND.to_string(), amount: Uint128::new(3000) }).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info(ADMIN, &[]), ExecuteMsg::AddRewards{
+            amount: Uint128::new(400) }).unwrap();
+        assert_eq!(Uint128::new(1000), staked_balance(deps.as_ref(), STAKER).balance);
+
+        // Rewards are shared out when the epoch ends
+        let mut env = mock_env();
+        env.block.height += 100;
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::SharesFor{ amount: Uint128::new(1100) }).unwrap();
+        let shares: SharesForResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128::new(1000), shares.shares);
+        execute(deps.as_mut(), env.clone(), mock_info(STAKER, &[]), ExecuteMsg::Rebase{}).unwrap();
+        assert_eq!(Uint128::new(1100), staked_balance(deps.as_ref(), STAKER).balance);
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Index{}).unwrap();
+        let index: IndexResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128::new(1_100_000_000), index.index);
+
+        let res = execute(deps.as_mut(), env.clone(), mock_info(STAKER, &[]), ExecuteMsg::Unstake{
+            amount: Uint128::new(1101) });
+        match res {
+            Err(ContractError::InsufficientStake{}) => {},
+            _ => panic!("Must return insufficient stake error")
+        }
+        let res = execute(deps.as_mut(), env, mock_info(STAKER, &[]), ExecuteMsg::Unstake{
+            amount: Uint128::new(1100) }).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+            funds: vec![],
+            contract_addr: PHS.to_string(),
+            msg: to_binary(&TreasuryExecuteMsg::Transfer{
+                recipient: STAKER.to_string(),
+                amount: Uint128::new(1100)
+            }).unwrap()
+        }));
+        assert!(staked_balance(deps.as_ref(), STAKER).shares.is_zero());
+    }
+
+    #[test]
+    fn exempt_stakers_and_share_transfers() {
+        let mut deps = mock_dependencies(&[]);
+        instantiate(deps.as_mut(), mock_env(), mock_info(ADMIN, &[]), init_msg(2)).unwrap();
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info(BOND, &[]), ExecuteMsg::SetWarmupExempt{
+            address: BOND.to_string(), exempt: true });
+        match res {
+            Err(ContractError::Unauthorized{}) => {},
+            _ => panic!("Must return unauthorized error")
+        }
+        execute(deps.as_mut(), mock_env(), mock_info(ADMIN, &[]), ExecuteMsg::SetWarmupExempt{
+            address: BOND.to_string(), exempt: true }).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::WarmupExempt{ address: BOND.to_string() }).unwrap();
+        let exempt: WarmupExemptResponse = from_binary(&res).unwrap();
+        assert!(exempt.exempt);
+
+        // A bond staking for itself skips the warmup and hands out shares as positions vest
+        execute(deps.as_mut(), mock_env(), mock_info(BOND, &[]), ExecuteMsg::Stake{
+            recipient: BOND.to_string(), amount: Uint128::new(1000) }).unwrap();
+        assert_eq!(Uint128::new(1000), staked_balance(deps.as_ref(), BOND).shares);
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info(BOND, &[]), ExecuteMsg::TransferShares{
+            recipient: STAKER.to_string(), shares: Uint128::new(1001) });
+        match res {
+            Err(ContractError::InsufficientStake{}) => {},
+            _ => panic!("Must return insufficient stake error")
+        }
+        execute(deps.as_mut(), mock_env(), mock_info(BOND, &[]), ExecuteMsg::TransferShares{
+            recipient: STAKER.to_string(), shares: Uint128::new(400) }).unwrap();
+        assert_eq!(Uint128::new(600), staked_balance(deps.as_ref(), BOND).shares);
+        assert_eq!(Uint128::new(400), staked_balance(deps.as_ref(), STAKER).shares);
+
+        // Sending shares to a contract notifies it, like a cw20 Send
+        let msg = to_binary("wrap").unwrap();
+        let res = execute(deps.as_mut(), mock_env(), mock_info(STAKER, &[]), ExecuteMsg::SendShares{
+            contract: PHS.to_string(), shares: Uint128::new(100), msg: msg.clone() }).unwrap();
+        assert_eq!(res.messages[0].msg, ReceiveSharesMsg{
+            sender: STAKER.to_string(),
+            shares: Uint128::new(100),
+            msg
+        }.into_cosmos_msg(PHS).unwrap());
+        assert_eq!(Uint128::new(100), staked_balance(deps.as_ref(), PHS).shares);
+    }
+
+    #[test]
+    fn lockup_boosts() {
+        let mut deps = mock_dependencies(&[]);
+        instantiate(deps.as_mut(), mock_env(), mock_info(ADMIN, &[]), init_msg(0)).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info(BOND, &[]), ExecuteMsg::Stake{
+            recipient: STAKER.to_string(), amount: Uint128::new(1000) }).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info(BOND, &[]), ExecuteMsg::Stake{
+            recipient: BOND.to_string(), amount: Uint128::new(1000) }).unwrap();
+
+        let lock_msg = ExecuteMsg::Lock{ shares: Uint128::new(1000), epochs: 3 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(STAKER, &[]), lock_msg.clone());
+        match res {
+            Err(ContractError::InvalidLockup{}) => {},
+            _ => panic!("Must return invalid lockup error")
+        }
+        let boosts = vec![LockBoost{ epochs: 2, boost: 20000 }, LockBoost{ epochs: 5, boost: 30000 }];
+        execute(deps.as_mut(), mock_env(), mock_info(ADMIN, &[]), ExecuteMsg::SetLockBoosts{ boosts }).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info(STAKER, &[]), lock_msg).unwrap();
+        assert!(staked_balance(deps.as_ref(), STAKER).shares.is_zero());
+
+        // The 2x lock earns double what the same unlocked stake does
+        execute(deps.as_mut(), mock_env(), mock_info(ADMIN, &[]), ExecuteMsg::AddRewards{
+            amount: Uint128::new(300) }).unwrap();
+        let mut env = mock_env();
+        env.block.height += 100;
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Locks{ address: STAKER.to_string() }).unwrap();
+        let locks: LocksResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![LockResponse{
+            id: 0,
+            shares: Uint128::new(1000),
+            balance: Uint128::new(1100),
+            boost: 20000,
+            unlock_epoch: 3,
+            pending_rewards: Uint128::new(100)
+        }], locks.locks);
+        execute(deps.as_mut(), env.clone(), mock_info(STAKER, &[]), ExecuteMsg::Rebase{}).unwrap();
+        assert_eq!(Uint128::new(1100), staked_balance(deps.as_ref(), BOND).balance);
+
+        let res = execute(deps.as_mut(), env.clone(), mock_info(STAKER, &[]), ExecuteMsg::Unlock{ lock_id: 0 });
+        match res {
+            Err(ContractError::LockActive{ unlock_epoch: 3 }) => {},
+            _ => panic!("Must return lock active error")
+        }
+        env.block.height += 200;
+        execute(deps.as_mut(), env, mock_info(STAKER, &[]), ExecuteMsg::Unlock{ lock_id: 0 }).unwrap();
+        assert_eq!(Uint128::new(1199), staked_balance(deps.as_ref(), STAKER).balance);
+        let pool = POOL.load(deps.as_ref().storage).unwrap();
+        assert!(pool.boost_weight.is_zero());
+        assert!(pool.boosted.is_zero());
+    }
+
+    #[test]
+    fn unstake_cooldown() {
+        let mut deps = mock_dependencies(&[]);
+        instantiate(deps.as_mut(), mock_env(), mock_info(ADMIN, &[]), init_msg(0)).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info(ADMIN, &[]), ExecuteMsg::SetCooldownPeriod{ period: 1 }).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info(BOND, &[]), ExecuteMsg::Stake{
+            recipient: STAKER.to_string(), amount: Uint128::new(1000) }).unwrap();
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info(STAKER, &[]), ExecuteMsg::Unstake{
+            amount: Uint128::new(400) }).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(Uint128::new(600), staked_balance(deps.as_ref(), STAKER).balance);
+        let res = execute(deps.as_mut(), mock_env(), mock_info(STAKER, &[]), ExecuteMsg::ClaimCooldown{});
+        match res {
+            Err(ContractError::CooldownActive{ expiry: 1 }) => {},
+            _ => panic!("Must return cooldown active error")
+        }
+
+        let mut env = mock_env();
+        env.block.height += 100;
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Cooldown{ address: STAKER.to_string() }).unwrap();
+        let cooldown: CooldownResponse = from_binary(&res).unwrap();
+        assert_eq!(CooldownResponse{ amount: Uint128::new(400), expiry: 1, claimable: true }, cooldown);
+        let res = execute(deps.as_mut(), env, mock_info(STAKER, &[]), ExecuteMsg::ClaimCooldown{}).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+            funds: vec![],
+            contract_addr: PHS.to_string(),
+            msg: to_binary(&TreasuryExecuteMsg::Transfer{
+                recipient: STAKER.to_string(),
+                amount: Uint128::new(400)
+            }).unwrap()
+        }));
+    }
+}
@@ -0,0 +1,41 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Amount must be greater than zero")]
+    InvalidZeroAmount{},
+
+    #[error("Nothing in warmup")]
+    NoWarmup{},
+
+    #[error("Warmup ends at epoch {expiry}")]
+    WarmupActive{ expiry: u64 },
+
+    #[error("Insufficient staked balance")]
+    InsufficientStake{},
+
+    #[error("Nothing in cooldown")]
+    NoCooldown{},
+
+    #[error("Cooldown ends at epoch {expiry}")]
+    CooldownActive{ expiry: u64 },
+
+    #[error("No boost tier for this lockup")]
+    InvalidLockup{},
+
+    #[error("Boost tiers need a positive lockup and a boost of at least 1x")]
+    InvalidBoost{},
+
+    #[error("No such lock")]
+    NoLock{},
+
+    #[error("Lock ends at epoch {unlock_epoch}")]
+    LockActive{ unlock_epoch: u64 },
+}
@@ -0,0 +1,187 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{to_binary, Binary, CosmosMsg, StdResult, Uint128, WasmMsg};
+
+use crate::state::LockBoost;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub admin: String,
+    pub phs: String,
+    pub epoch_length: u64,
+    pub first_epoch_block: u64,
+    pub warmup_period: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    // Pulls `amount` PHS from the sender, it needs an allowance on the PHS token first
+    Stake{
+        recipient: String,
+        amount: Uint128
+    },
+    ClaimWarmup{
+        recipient: String
+    },
+    // Pays out right away, or after the cooldown period through ClaimCooldown
+    Unstake{
+        amount: Uint128
+    },
+    ClaimCooldown{},
+    // Locks staked shares for at least `epochs`, the boost comes from the best tier that fits
+    Lock{
+        shares: Uint128,
+        epochs: u64
+    },
+    // Returns the locked shares and stakes the boost rewards they earned
+    Unlock{
+        lock_id: u64
+    },
+    // Shares are not rebased, moving them moves a fixed slice of the staked PHS
+    TransferShares{
+        recipient: String,
+        shares: Uint128
+    },
+    // Moves shares to a contract and calls its ReceiveShares handler with `msg`
+    SendShares{
+        contract: String,
+        shares: Uint128,
+        msg: Binary
+    },
+    // Pulls `amount` PHS from the sender and hands it to stakers at the next rebase
+    AddRewards{
+        amount: Uint128
+    },
+    Rebase{},
+    SetWarmupPeriod{
+        period: u64
+    },
+    SetWarmupExempt{
+        address: String,
+        exempt: bool
+    },
+    SetCooldownPeriod{
+        period: u64
+    },
+    // Replaces the boost tiers, existing locks keep the boost they were made with
+    SetLockBoosts{
+        boosts: Vec<LockBoost>
+    }
+}
+
+// What SendShares delivers to the receiving contract, shaped like a cw20 Send
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReceiveSharesMsg {
+    pub sender: String,
+    pub shares: Uint128,
+    pub msg: Binary
+}
+
+impl ReceiveSharesMsg {
+    pub fn into_binary(self) -> StdResult<Binary> {
+        to_binary(&ReceiverExecuteMsg::ReceiveShares(self))
+    }
+
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        Ok(CosmosMsg::Wasm(WasmMsg::Execute{
+            contract_addr: contract_addr.into(),
+            msg: self.into_binary()?,
+            funds: vec![]
+        }))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum ReceiverExecuteMsg {
+    ReceiveShares(ReceiveSharesMsg)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config{},
+    Epoch{},
+    Index{},
+    StakedBalance{address: String},
+    WarmupInfo{address: String},
+    WarmupExempt{address: String},
+    SharesFor{amount: Uint128},
+    LockBoosts{},
+    Locks{address: String},
+    Cooldown{address: String}
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub admin: Option<String>,
+    pub phs: String,
+    pub epoch_length: u64,
+    pub warmup_period: u64,
+    pub cooldown_period: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EpochResponse {
+    pub number: u64,
+    pub end_block: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IndexResponse {
+    pub index: Uint128
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakedBalanceResponse {
+    pub shares: Uint128,
+    pub balance: Uint128
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SharesForResponse {
+    pub shares: Uint128
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct WarmupInfoResponse {
+    pub deposit: Uint128,
+    pub shares: Uint128,
+    pub balance: Uint128,
+    pub expiry: u64,
+    pub claimable: bool
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WarmupExemptResponse {
+    pub exempt: bool
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockBoostsResponse {
+    pub boosts: Vec<LockBoost>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockResponse {
+    pub id: u64,
+    pub shares: Uint128,
+    pub balance: Uint128,
+    pub boost: u64,
+    pub unlock_epoch: u64,
+    pub pending_rewards: Uint128
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LocksResponse {
+    pub locks: Vec<LockResponse>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct CooldownResponse {
+    pub amount: Uint128,
+    pub expiry: u64,
+    pub claimable: bool
+}
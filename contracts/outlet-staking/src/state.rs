@@ -0,0 +1,91 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Addr, Uint128};
+use cw_storage_plus::{Item, Map, U64Key};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub phs: Addr,
+    pub epoch_length: u64,
+    pub warmup_period: u64,
+    // Epochs unstaked PHS waits before it can be claimed, zero pays out right away
+    #[serde(default)]
+    pub cooldown_period: u64
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Epoch {
+    pub number: u64,
+    pub end_block: u64
+}
+
+pub const EPOCH: Item<Epoch> = Item::new("epoch");
+
+// PHS owned by stakers and the shares it is split into, rewards wait in pending until the next rebase
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct Pool {
+    pub staked: Uint128,
+    pub shares: Uint128,
+    pub pending_rewards: Uint128,
+    // Boost on top of the locked shares, counted in shares, that earns a share of every rebase
+    #[serde(default)]
+    pub boost_weight: Uint128,
+    // Boost rewards per unit of boost weight so far, scaled by INDEX_PRECISION
+    #[serde(default)]
+    pub boost_index: Uint128,
+    // PHS set aside for boost rewards that locks have not claimed yet
+    #[serde(default)]
+    pub boosted: Uint128
+}
+
+pub const POOL: Item<Pool> = Item::new("pool");
+
+pub const SHARES: Map<&Addr, Uint128> = Map::new("shares");
+
+// Stakes in warmup already earn rebases but only count as staked once claimed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WarmupClaim {
+    pub deposit: Uint128,
+    pub shares: Uint128,
+    pub expiry: u64
+}
+
+pub const WARMUP: Map<&Addr, WarmupClaim> = Map::new("warmup");
+
+// Contracts that stake on behalf of others, like bonds paying out in shares, skip the warmup
+pub const WARMUP_EXEMPT: Map<&Addr, bool> = Map::new("warmup_exempt");
+
+// Locking for at least `epochs` multiplies a lock's rewards by `boost`, where 10000 is 1x
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockBoost {
+    pub epochs: u64,
+    pub boost: u64
+}
+
+pub const LOCK_BOOSTS: Item<Vec<LockBoost>> = Item::new("lock_boosts");
+
+// Locked shares stay in the pool but cannot be moved or unstaked until the unlock epoch
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Lock {
+    pub shares: Uint128,
+    pub boost: u64,
+    pub weight: Uint128,
+    pub unlock_epoch: u64,
+    // boost_index when the lock was made, rewards before that belong to others
+    pub index_start: Uint128
+}
+
+pub const LOCKS: Map<(&Addr, U64Key), Lock> = Map::new("locks");
+pub const LOCK_COUNT: Item<u64> = Item::new("lock_count");
+
+// Unstaked PHS stops earning and waits out the cooldown before it is paid
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cooldown {
+    pub amount: Uint128,
+    pub expiry: u64
+}
+
+pub const COOLDOWNS: Map<&Addr, Cooldown> = Map::new("cooldowns");
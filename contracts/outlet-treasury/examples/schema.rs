@@ -3,7 +3,8 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use outlet_treasury::msg::{CountResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+use outlet_treasury::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, TokenInfoResponse, BalanceResponse, AllowanceResponse,
+                           PauseStatusResponse, AdminResponse};
 use outlet_treasury::state::State;
 
 fn main() {
@@ -16,5 +17,9 @@ fn main() {
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(State), &out_dir);
-    export_schema(&schema_for!(CountResponse), &out_dir);
+    export_schema(&schema_for!(TokenInfoResponse), &out_dir);
+    export_schema(&schema_for!(BalanceResponse), &out_dir);
+    export_schema(&schema_for!(AllowanceResponse), &out_dir);
+    export_schema(&schema_for!(PauseStatusResponse), &out_dir);
+    export_schema(&schema_for!(AdminResponse), &out_dir);
 }
@@ -5,10 +5,13 @@ use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response,
 use cw2::set_contract_version;
 
 use crate::error::ContractError;
-use crate::msg::{ ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{State, STATE, ReserveDepositors, RESERVE_DEPOSITORS, ReserveSpenders, RESERVE_SPENDERS};
+use crate::msg::{ ExecuteMsg, InstantiateMsg, QueryMsg, PauseStatusResponse,
+                    AdminResponse};
+use crate::state::{State, STATE, ReserveDepositors, RESERVE_DEPOSITORS, ReserveSpenders, RESERVE_SPENDERS,
+                    PauseStatus, PAUSE_STATUS, PendingAdmin, PENDING_ADMIN};
 
 use cw_controllers::Admin;
+use cw20::Expiration;
 use cw20_base::state::{TokenInfo, TOKEN_INFO, MinterData};
 use cw20_base::contract::{execute_burn, execute_mint, execute_transfer, query_balance, query_token_info};
 use cw20_base::allowances::{execute_transfer_from, execute_increase_allowance, query_allowance};
@@ -18,6 +21,7 @@ const CONTRACT_NAME: &str = "crates.io:outlet-treasury";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 static TREASURY_ADMIN: &Admin = &Admin::new("treasury_admin");
+static TREASURY_GUARDIAN: &Admin = &Admin::new("treasury_guardian");
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -31,6 +35,13 @@ pub fn instantiate(
     let admin_addr = deps.api.addr_validate(&msg.admin)?;
     TREASURY_ADMIN.set(deps.branch(), Some(admin_addr))?;
 
+    let guardian_addr = match msg.guardian {
+        Some(guardian) => Some(deps.api.addr_validate(&guardian)?),
+        None => None
+    };
+    TREASURY_GUARDIAN.set(deps.branch(), guardian_addr)?;
+    PAUSE_STATUS.save(deps.storage, &PauseStatus::default())?;
+
     let state = State{
         total_reserves: 0,
         total_debt: 0
@@ -64,6 +75,59 @@ fn only_admin(deps: DepsMut, info: MessageInfo) -> Result< (),ContractError>{
     }; 
 }
 
+// Admin handover is two-step: the current admin proposes, the new admin accepts before the expiry
+pub fn propose_new_admin(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    expiry: Option<Expiration>
+) -> Result<Response, ContractError>{
+    only_admin(deps.branch(), info)?;
+    let pending_admin = PendingAdmin{
+        address: deps.api.addr_validate(&address)?,
+        expiry
+    };
+    PENDING_ADMIN.save(deps.storage, &pending_admin)?;
+    Ok(Response::new()
+        .add_attribute("method", "propose_new_admin")
+        .add_attribute("pending_admin", address))
+}
+
+pub fn accept_admin(mut deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError>{
+    let pending_admin = match PENDING_ADMIN.may_load(deps.storage)? {
+        Some(pending_admin) => pending_admin,
+        None => return Err(ContractError::NoPendingAdmin{})
+    };
+    if pending_admin.address != info.sender {
+        return Err(ContractError::Unauthorized{})
+    }
+    if let Some(expiry) = pending_admin.expiry {
+        if expiry.is_expired(&env.block) {
+            return Err(ContractError::AdminProposalExpired{})
+        }
+    }
+    TREASURY_ADMIN.set(deps.branch(), Some(pending_admin.address))?;
+    PENDING_ADMIN.remove(deps.storage);
+    Ok(Response::new()
+        .add_attribute("method", "accept_admin")
+        .add_attribute("admin", info.sender))
+}
+
+pub fn renounce_admin(mut deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError>{
+    only_admin(deps.branch(), info)?;
+    TREASURY_ADMIN.set(deps.branch(), None)?;
+    PENDING_ADMIN.remove(deps.storage);
+    Ok(Response::new().add_attribute("method", "renounce_admin"))
+}
+
+fn only_admin_or_guardian(deps: DepsMut, info: MessageInfo) -> Result< (),ContractError>{
+    let is_guardian = TREASURY_GUARDIAN.is_admin(deps.as_ref(), &info.sender)?;
+    if is_guardian {
+        return Ok(())
+    }
+    only_admin(deps, info)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -76,16 +140,77 @@ pub fn execute(
         ExecuteMsg::AddRemoveSpender{ address } => add_remove_spender(deps, info, address),
         ExecuteMsg::Deposit{ amount, profit} => deposit(deps, _env, info, amount, profit),
         ExecuteMsg::IncreaseAllowance{spender,amount} => Ok(execute_increase_allowance(deps, _env, info, spender, amount, None).unwrap()),
-        ExecuteMsg::Transfer{recipient, amount} => Ok(execute_transfer(deps, _env, info, recipient, amount).unwrap()),
-        ExecuteMsg::TransferFrom{owner, recipient, amount} => Ok(execute_transfer_from(deps, _env, info, owner, recipient, amount).unwrap()),
-        ExecuteMsg::Withdraw{ recipient, amount } => withdraw(deps, _env, info, recipient, amount)
+        ExecuteMsg::Transfer{recipient, amount} => {
+            assert_not_paused(deps.as_ref(), |pause_status| pause_status.transfers)?;
+            Ok(execute_transfer(deps, _env, info, recipient, amount).unwrap())
+        },
+        ExecuteMsg::TransferFrom{owner, recipient, amount} => {
+            assert_not_paused(deps.as_ref(), |pause_status| pause_status.transfers)?;
+            Ok(execute_transfer_from(deps, _env, info, owner, recipient, amount).unwrap())
+        },
+        ExecuteMsg::Withdraw{ recipient, amount } => withdraw(deps, _env, info, recipient, amount),
+        ExecuteMsg::Burn{amount} => execute_burn(deps, _env, info, amount).map_err(ContractError::from),
+        ExecuteMsg::SetGuardian{ address } => set_guardian(deps, info, address),
+        ExecuteMsg::Pause{ deposits, withdrawals, transfers } => pause(deps, info, deposits, withdrawals, transfers),
+        ExecuteMsg::Unpause{ deposits, withdrawals, transfers } => unpause(deps, info, deposits, withdrawals, transfers),
+        ExecuteMsg::ProposeNewAdmin{ address, expiry } => propose_new_admin(deps, info, address, expiry),
+        ExecuteMsg::AcceptAdmin{} => accept_admin(deps, _env, info),
+        ExecuteMsg::RenounceAdmin{} => renounce_admin(deps, info)
 
         // ExecuteMsg::Increment {} => try_increment(deps),
         // ExecuteMsg::Reset { count } => try_reset(deps, info, count),
     }
 }
 
+fn assert_not_paused(deps: Deps, paused: fn(&PauseStatus) -> bool) -> Result<(), ContractError> {
+    let pause_status = PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default();
+    if paused(&pause_status) {
+        return Err(ContractError::Paused{})
+    }
+    Ok(())
+}
+
+pub fn set_guardian(mut deps: DepsMut, info: MessageInfo, address: Option<String>) -> Result<Response, ContractError> {
+    only_admin(deps.branch(), info)?;
+    let guardian = match address {
+        Some(address) => Some(deps.api.addr_validate(&address)?),
+        None => None
+    };
+    TREASURY_GUARDIAN.set(deps.branch(), guardian)?;
+    Ok(Response::default())
+}
+
+// The guardian can pause on its own, lifting a pause is left to the admin
+pub fn pause(mut deps: DepsMut, info: MessageInfo, deposits: bool, withdrawals: bool, transfers: bool) -> Result<Response, ContractError> {
+    only_admin_or_guardian(deps.branch(), info)?;
+    let mut pause_status = PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default();
+    pause_status.deposits = pause_status.deposits || deposits;
+    pause_status.withdrawals = pause_status.withdrawals || withdrawals;
+    pause_status.transfers = pause_status.transfers || transfers;
+    PAUSE_STATUS.save(deps.storage, &pause_status)?;
+    Ok(Response::new()
+        .add_attribute("method", "pause")
+        .add_attribute("deposits", pause_status.deposits.to_string())
+        .add_attribute("withdrawals", pause_status.withdrawals.to_string())
+        .add_attribute("transfers", pause_status.transfers.to_string()))
+}
+
+pub fn unpause(mut deps: DepsMut, info: MessageInfo, deposits: bool, withdrawals: bool, transfers: bool) -> Result<Response, ContractError> {
+    only_admin(deps.branch(), info)?;
+    let mut pause_status = PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default();
+    pause_status.deposits = pause_status.deposits && !deposits;
+    pause_status.withdrawals = pause_status.withdrawals && !withdrawals;
+    pause_status.transfers = pause_status.transfers && !transfers;
+    PAUSE_STATUS.save(deps.storage, &pause_status)?;
+    Ok(Response::new()
+        .add_attribute("method", "unpause")
+        .add_attribute("deposits", pause_status.deposits.to_string())
+        .add_attribute("withdrawals", pause_status.withdrawals.to_string())
+        .add_attribute("transfers", pause_status.transfers.to_string()))
+}
+
 pub fn withdraw(mut deps: DepsMut, env: Env, info: MessageInfo, recipient: String, amount: u64) -> Result<Response, ContractError> {
+    assert_not_paused(deps.as_ref(), |pause_status| pause_status.withdrawals)?;
     let rsd = RESERVE_SPENDERS.load(deps.storage)?;
     let sender = info.clone().sender;
     let value = amount * 10u64.pow(3);
@@ -109,6 +234,7 @@ pub fn withdraw(mut deps: DepsMut, env: Env, info: MessageInfo, recipient: Strin
 }
 
 pub fn deposit(mut deps: DepsMut, env: Env, info: MessageInfo, amount: u64, profit: u64) -> Result<Response, ContractError>{
+    assert_not_paused(deps.as_ref(), |pause_status| pause_status.deposits)?;
     let rds = RESERVE_DEPOSITORS.load(deps.storage)?;
     let is_depositor = rds.depositors.iter().any(|x| x == &info.clone().sender);
     
@@ -218,10 +344,33 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::TokenInfo{} => to_binary(&query_token_info(deps)?),
         QueryMsg::QueryAllowance{owner, spender} => to_binary(&query_allowance(deps, owner, spender)?),
-        QueryMsg::Balance{address} => to_binary(&query_balance(deps, address)?)
+        QueryMsg::Balance{address} => to_binary(&query_balance(deps, address)?),
+        QueryMsg::PauseStatus{} => to_binary(&query_pause_status(deps)?),
+        QueryMsg::Admin{} => to_binary(&query_admin(deps)?)
     }
 }
 
+fn query_admin(deps: Deps) -> StdResult<AdminResponse> {
+    let admin = TREASURY_ADMIN.get(deps)?;
+    let pending_admin = PENDING_ADMIN.may_load(deps.storage)?;
+    Ok(AdminResponse{
+        admin: admin.map(String::from),
+        pending_admin: pending_admin.as_ref().map(|pending| pending.address.to_string()),
+        pending_expiry: pending_admin.and_then(|pending| pending.expiry)
+    })
+}
+
+fn query_pause_status(deps: Deps) -> StdResult<PauseStatusResponse> {
+    let pause_status = PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default();
+    let guardian = TREASURY_GUARDIAN.get(deps)?;
+    Ok(PauseStatusResponse{
+        guardian: guardian.map(String::from),
+        deposits_paused: pause_status.deposits,
+        withdrawals_paused: pause_status.withdrawals,
+        transfers_paused: pause_status.transfers
+    })
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -235,7 +384,7 @@ mod tests {
 
         let addr = "terra1e8ryd9ezefuucd4mje33zdms9m2s90m57878v9";
 
-        let msg = InstantiateMsg { admin: addr.to_string() };
+        let msg = InstantiateMsg { admin: addr.to_string(), guardian: None };
         let info = mock_info(addr, &coins(1000, "earth"));
 
         // we can just call .unwrap() to assert this was a success
@@ -253,5 +402,101 @@ mod tests {
         println!("{:#?}", val);
     }
 
-    
+    #[test]
+    fn pause() {
+        let mut deps = mock_dependencies(&[]);
+
+        let addr = "terra1e8ryd9ezefuucd4mje33zdms9m2s90m57878v9";
+        let guardian = "terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8";
+
+        let msg = InstantiateMsg { admin: addr.to_string(), guardian: Some(guardian.to_string()) };
+        let info = mock_info(addr, &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        add_remove_depositor(deps.as_mut(), info.clone(), addr.to_string()).unwrap();
+
+        let pause_msg = ExecuteMsg::Pause{ deposits: true, withdrawals: false, transfers: true };
+        execute(deps.as_mut(), mock_env(), mock_info(guardian, &[]), pause_msg).unwrap();
+
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Deposit{ amount: 500, profit: 50 });
+        match res {
+            Err(ContractError::Paused{}) => {},
+            _ => panic!("Must return paused error")
+        }
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Transfer{ recipient: guardian.to_string(), amount: Uint128::from(1u128) });
+        match res {
+            Err(ContractError::Paused{}) => {},
+            _ => panic!("Must return paused error")
+        }
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::PauseStatus{}).unwrap();
+        let status: PauseStatusResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(guardian.to_string()), status.guardian);
+        assert!(status.deposits_paused);
+        assert!(!status.withdrawals_paused);
+        assert!(status.transfers_paused);
+
+        // Only the admin lifts the pause, one action at a time
+        let unpause_msg = ExecuteMsg::Unpause{ deposits: true, withdrawals: false, transfers: false };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(guardian, &[]), unpause_msg.clone());
+        match res {
+            Err(ContractError::Unauthorized{}) => {},
+            _ => panic!("Must return unauthorized error")
+        }
+        execute(deps.as_mut(), mock_env(), info.clone(), unpause_msg).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Deposit{ amount: 500, profit: 50 }).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::PauseStatus{}).unwrap();
+        let status: PauseStatusResponse = from_binary(&res).unwrap();
+        assert!(!status.deposits_paused);
+        assert!(status.transfers_paused);
+    }
+
+    #[test]
+    fn burn_more_than_balance() {
+        let mut deps = mock_dependencies(&[]);
+
+        let addr = "terra1e8ryd9ezefuucd4mje33zdms9m2s90m57878v9";
+
+        let msg = InstantiateMsg { admin: addr.to_string(), guardian: None };
+        let info = mock_info(addr, &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Burn{ amount: Uint128::from(1u128) });
+        match res {
+            Err(ContractError::Cw20(_)) => {},
+            _ => panic!("Must return cw20 error")
+        }
+    }
+
+    #[test]
+    fn renounce_admin() {
+        let mut deps = mock_dependencies(&[]);
+
+        let addr = "terra1e8ryd9ezefuucd4mje33zdms9m2s90m57878v9";
+        let multisig = "terra1dcegyrekltswvyy0xy69ydgxn9x8x32zdtapd8";
+
+        let msg = InstantiateMsg { admin: addr.to_string(), guardian: None };
+        let info = mock_info(addr, &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let propose_msg = ExecuteMsg::ProposeNewAdmin{ address: multisig.to_string(), expiry: None };
+        execute(deps.as_mut(), mock_env(), info.clone(), propose_msg).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info(multisig, &[]), ExecuteMsg::AcceptAdmin{}).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Admin{}).unwrap();
+        let value: AdminResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(multisig.to_string()), value.admin);
+
+        execute(deps.as_mut(), mock_env(), mock_info(multisig, &[]), ExecuteMsg::RenounceAdmin{}).unwrap();
+
+        let res = add_remove_depositor(deps.as_mut(), mock_info(multisig, &[]), addr.to_string());
+        match res {
+            Err(ContractError::Unauthorized{}) => {},
+            _ => panic!("Must return unauthorized error")
+        }
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Admin{}).unwrap();
+        let value: AdminResponse = from_binary(&res).unwrap();
+        assert_eq!(None, value.admin);
+    }
 }
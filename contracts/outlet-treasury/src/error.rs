@@ -6,8 +6,20 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Cw20(#[from] cw20_base::ContractError),
+
     #[error("Unauthorized")]
     Unauthorized {},
+
+    #[error("Paused")]
+    Paused {},
+
+    #[error("No pending admin")]
+    NoPendingAdmin{},
+
+    #[error("Admin proposal expired")]
+    AdminProposalExpired{},
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
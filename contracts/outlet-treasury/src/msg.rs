@@ -2,10 +2,12 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_std::Uint128;
+use cw20::Expiration;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
-   pub admin: String
+   pub admin: String,
+   pub guardian: Option<String>
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -37,8 +39,29 @@ pub enum ExecuteMsg {
     Withdraw{
         recipient: String,
         amount: u64
-    }
-
+    },
+    Burn{
+        amount: Uint128
+    },
+    SetGuardian{
+        address: Option<String>
+    },
+    Pause{
+        deposits: bool,
+        withdrawals: bool,
+        transfers: bool
+    },
+    Unpause{
+        deposits: bool,
+        withdrawals: bool,
+        transfers: bool
+    },
+    ProposeNewAdmin{
+        address: String,
+        expiry: Option<Expiration>
+    },
+    AcceptAdmin{},
+    RenounceAdmin{}
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -51,11 +74,25 @@ pub enum QueryMsg {
     QueryAllowance{
         owner: String,
         spender: String
-    }
+    },
+    PauseStatus{},
+    Admin{}
+}
+
+// Token queries are answered by cw20-base, so the responses are the standard cw20 ones
+pub use cw20::{TokenInfoResponse, BalanceResponse, AllowanceResponse};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PauseStatusResponse {
+    pub guardian: Option<String>,
+    pub deposits_paused: bool,
+    pub withdrawals_paused: bool,
+    pub transfers_paused: bool
 }
 
-// We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct CountResponse {
-    pub count: i32,
+pub struct AdminResponse {
+    pub admin: Option<String>,
+    pub pending_admin: Option<String>,
+    pub pending_expiry: Option<Expiration>
 }
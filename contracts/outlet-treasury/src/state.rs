@@ -2,6 +2,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_std::Addr;
+use cw20::Expiration;
 use cw_storage_plus::Item;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -24,4 +25,19 @@ pub struct ReserveSpenders {
 }
 pub const RESERVE_SPENDERS: Item<ReserveSpenders> = Item::new("reserve_spenders");
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PauseStatus {
+    pub deposits: bool,
+    pub withdrawals: bool,
+    pub transfers: bool
+}
+
+pub const PAUSE_STATUS: Item<PauseStatus> = Item::new("pause_status");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingAdmin {
+    pub address: Addr,
+    pub expiry: Option<Expiration>
+}
 
+pub const PENDING_ADMIN: Item<PendingAdmin> = Item::new("pending_admin");
@@ -0,0 +1,277 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{to_binary, from_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, CosmosMsg,
+                    WasmMsg, QueryRequest, WasmQuery, Uint128};
+use cw2::set_contract_version;
+
+use crate::error::ContractError;
+use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg, ConfigResponse, WrappedToStakedResponse,
+                StakedToWrappedResponse};
+use crate::state::{Config, CONFIG};
+
+use cw20_base::state::{TokenInfo, TOKEN_INFO, MinterData};
+use cw20_base::contract::{execute_burn, execute_mint, execute_transfer, execute_send, query_balance, query_token_info};
+use cw20_base::allowances::{execute_transfer_from, execute_send_from, execute_increase_allowance,
+                            execute_decrease_allowance, query_allowance};
+use outlet_staking::contract::INDEX_PRECISION;
+use outlet_staking::msg::{ExecuteMsg as StakingExecuteMsg, QueryMsg as StakingQueryMsg, IndexResponse,
+                        ReceiveSharesMsg};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:outlet-wrapped-staked";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    CONFIG.save(deps.storage, &Config{ staking: deps.api.addr_validate(&msg.staking)? })?;
+
+    // Only this contract mints, one wsPHS for every share it holds
+    let token_data = TokenInfo{
+        name: "Wrapped Staked Phase".to_string(),
+        symbol: "wsPHS".to_string(),
+        decimals: 9,
+        total_supply: Uint128::zero(),
+        mint: Some(MinterData{
+            minter: env.contract.address,
+            cap: None
+        })
+    };
+    TOKEN_INFO.save(deps.storage, &token_data)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("owner", info.sender))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::ReceiveShares(msg) => receive_shares(deps, env, info, msg),
+        ExecuteMsg::Unwrap {amount} => unwrap(deps, env, info, amount),
+        ExecuteMsg::Transfer {recipient, amount} => Ok(execute_transfer(deps, env, info, recipient, amount)?),
+        ExecuteMsg::Send {contract, amount, msg} => Ok(execute_send(deps, env, info, contract, amount, msg)?),
+        ExecuteMsg::TransferFrom {owner, recipient, amount} =>
+            Ok(execute_transfer_from(deps, env, info, owner, recipient, amount)?),
+        ExecuteMsg::SendFrom {owner, contract, amount, msg} =>
+            Ok(execute_send_from(deps, env, info, owner, contract, amount, msg)?),
+        ExecuteMsg::IncreaseAllowance {spender, amount} =>
+            Ok(execute_increase_allowance(deps, env, info, spender, amount, None)?),
+        ExecuteMsg::DecreaseAllowance {spender, amount} =>
+            Ok(execute_decrease_allowance(deps, env, info, spender, amount, None)?)
+    }
+}
+
+// Shares arrive through the staking contract, which names the original sender
+pub fn receive_shares(deps: DepsMut, env: Env, info: MessageInfo, receive: ReceiveSharesMsg) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.staking {
+        return Err(ContractError::Unauthorized{})
+    }
+    match from_binary(&receive.msg)? {
+        ReceiveMsg::Wrap{recipient} => {
+            let recipient = match recipient {
+                Some(recipient) => deps.api.addr_validate(&recipient)?.to_string(),
+                None => receive.sender
+            };
+            wrap(deps, env, recipient, receive.shares)
+        }
+    }
+}
+
+fn wrap(deps: DepsMut, env: Env, recipient: String, shares: Uint128) -> Result<Response, ContractError> {
+    let sub_info = MessageInfo{
+        sender: env.contract.address.clone(),
+        funds: vec![]
+    };
+    execute_mint(deps, env, sub_info, recipient.clone(), shares)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "wrap")
+        .add_attribute("recipient", recipient)
+        .add_attribute("amount", shares))
+}
+
+pub fn unwrap(deps: DepsMut, env: Env, info: MessageInfo, amount: Uint128) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    execute_burn(deps, env, info.clone(), amount)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute{
+            funds: vec![],
+            contract_addr: config.staking.to_string(),
+            msg: to_binary(&StakingExecuteMsg::TransferShares{
+                recipient: info.sender.to_string(),
+                shares: amount
+            })?
+        }))
+        .add_attribute("method", "unwrap")
+        .add_attribute("recipient", info.sender)
+        .add_attribute("amount", amount))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::TokenInfo{} => to_binary(&query_token_info(deps)?),
+        QueryMsg::Balance{address} => to_binary(&query_balance(deps, address)?),
+        QueryMsg::Allowance{owner, spender} => to_binary(&query_allowance(deps, owner, spender)?),
+        QueryMsg::Config{} => to_binary(&query_config(deps)?),
+        QueryMsg::WrappedToStaked{amount} => to_binary(&query_wrapped_to_staked(deps, amount)?),
+        QueryMsg::StakedToWrapped{amount} => to_binary(&query_staked_to_wrapped(deps, amount)?)
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse{ staking: config.staking.to_string() })
+}
+
+fn query_wrapped_to_staked(deps: Deps, amount: Uint128) -> StdResult<WrappedToStakedResponse> {
+    let index = staking_index(deps)?;
+    Ok(WrappedToStakedResponse{ staked: amount.multiply_ratio(index, INDEX_PRECISION) })
+}
+
+fn query_staked_to_wrapped(deps: Deps, amount: Uint128) -> StdResult<StakedToWrappedResponse> {
+    let index = staking_index(deps)?;
+    Ok(StakedToWrappedResponse{ wrapped: amount.multiply_ratio(INDEX_PRECISION, index) })
+}
+
+// PHS value of one share, scaled by the staking contract's INDEX_PRECISION
+fn staking_index(deps: Deps) -> StdResult<Uint128> {
+    let config = CONFIG.load(deps.storage)?;
+    let res: IndexResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart{
+        contract_addr: config.staking.to_string(),
+        msg: to_binary(&StakingQueryMsg::Index{})?
+    }))?;
+    Ok(res.index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{from_slice, Empty, OwnedDeps, Querier, QuerierResult, SystemResult, ContractResult};
+    use cw20::{BalanceResponse, TokenInfoResponse};
+
+    const STAKING: &str = "terra1e8ryd9ezefuucd4mje33zdms9m2s90m57878v9";
+    const HOLDER: &str = "terra1q0fdx7yu5f6ddx5kckh4m5w4r9ytxvm0jrzfz8";
+
+    // Answers the staking contract's Index query with a share worth 1.1 PHS
+    struct StakingQuerier {
+        base: MockQuerier
+    }
+
+    impl Querier for StakingQuerier {
+        fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+            match from_slice::<QueryRequest<Empty>>(bin_request) {
+                Ok(QueryRequest::Wasm(WasmQuery::Smart{ .. })) => {
+                    let res = IndexResponse{ index: Uint128::new(1_100_000_000) };
+                    SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+                },
+                _ => self.base.raw_query(bin_request)
+            }
+        }
+    }
+
+    fn staking_dependencies() -> OwnedDeps<MockStorage, MockApi, StakingQuerier> {
+        OwnedDeps{
+            storage: MockStorage::default(),
+            api: MockApi::default(),
+            querier: StakingQuerier{ base: MockQuerier::<Empty>::new(&[]) }
+        }
+    }
+
+    fn wrap_msg(shares: u128) -> ExecuteMsg {
+        ExecuteMsg::ReceiveShares(ReceiveSharesMsg{
+            sender: HOLDER.to_string(),
+            shares: Uint128::new(shares),
+            msg: to_binary(&ReceiveMsg::Wrap{ recipient: None }).unwrap()
+        })
+    }
+
+    fn balance(deps: Deps, address: &str) -> Uint128 {
+        let res = query(deps, mock_env(), QueryMsg::Balance{ address: address.to_string() }).unwrap();
+        let balance: BalanceResponse = from_binary(&res).unwrap();
+        balance.balance
+    }
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = staking_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info(HOLDER, &[]), InstantiateMsg{ staking: STAKING.to_string() }).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::TokenInfo{}).unwrap();
+        let token: TokenInfoResponse = from_binary(&res).unwrap();
+        assert_eq!("wsPHS", token.symbol);
+        assert_eq!(9, token.decimals);
+        assert!(token.total_supply.is_zero());
+    }
+
+    #[test]
+    fn wrap_and_unwrap() {
+        let mut deps = staking_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info(HOLDER, &[]), InstantiateMsg{ staking: STAKING.to_string() }).unwrap();
+
+        // Only the staking contract can vouch for shares sent here
+        let res = execute(deps.as_mut(), mock_env(), mock_info(HOLDER, &[]), wrap_msg(1000));
+        match res {
+            Err(ContractError::Unauthorized{}) => {},
+            _ => panic!("Must return unauthorized error")
+        }
+        execute(deps.as_mut(), mock_env(), mock_info(STAKING, &[]), wrap_msg(1000)).unwrap();
+        assert_eq!(Uint128::new(1000), balance(deps.as_ref(), HOLDER));
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info(HOLDER, &[]), ExecuteMsg::Unwrap{
+            amount: Uint128::new(1001) });
+        match res {
+            Err(ContractError::Cw20(_)) => {},
+            _ => panic!("Must return cw20 error")
+        }
+        let res = execute(deps.as_mut(), mock_env(), mock_info(HOLDER, &[]), ExecuteMsg::Unwrap{
+            amount: Uint128::new(400) }).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+            funds: vec![],
+            contract_addr: STAKING.to_string(),
+            msg: to_binary(&StakingExecuteMsg::TransferShares{
+                recipient: HOLDER.to_string(),
+                shares: Uint128::new(400)
+            }).unwrap()
+        }));
+        assert_eq!(Uint128::new(600), balance(deps.as_ref(), HOLDER));
+
+        // A sender can wrap for someone else, the bond pays its owners this way
+        execute(deps.as_mut(), mock_env(), mock_info(STAKING, &[]), ExecuteMsg::ReceiveShares(ReceiveSharesMsg{
+            sender: HOLDER.to_string(),
+            shares: Uint128::new(500),
+            msg: to_binary(&ReceiveMsg::Wrap{ recipient: Some(STAKING.to_string()) }).unwrap()
+        })).unwrap();
+        assert_eq!(Uint128::new(600), balance(deps.as_ref(), HOLDER));
+        assert_eq!(Uint128::new(500), balance(deps.as_ref(), STAKING));
+    }
+
+    #[test]
+    fn conversions() {
+        let mut deps = staking_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info(HOLDER, &[]), InstantiateMsg{ staking: STAKING.to_string() }).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::WrappedToStaked{ amount: Uint128::new(1000) }).unwrap();
+        let staked: WrappedToStakedResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128::new(1100), staked.staked);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::StakedToWrapped{ amount: Uint128::new(1100) }).unwrap();
+        let wrapped: StakedToWrappedResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128::new(1000), wrapped.wrapped);
+    }
+}
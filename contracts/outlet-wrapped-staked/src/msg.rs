@@ -0,0 +1,93 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Binary, Uint128};
+use outlet_staking::msg::ReceiveSharesMsg;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub staking: String
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    // Called by the staking contract when shares are sent here
+    ReceiveShares(ReceiveSharesMsg),
+    Unwrap{
+        amount: Uint128
+    },
+    Transfer{
+        recipient: String,
+        amount: Uint128
+    },
+    Send{
+        contract: String,
+        amount: Uint128,
+        msg: Binary
+    },
+    TransferFrom{
+        owner: String,
+        recipient: String,
+        amount: Uint128
+    },
+    SendFrom{
+        owner: String,
+        contract: String,
+        amount: Uint128,
+        msg: Binary
+    },
+    IncreaseAllowance{
+        spender: String,
+        amount: Uint128
+    },
+    DecreaseAllowance{
+        spender: String,
+        amount: Uint128
+    }
+}
+
+// Message passed along with the shares in the staking contract's SendShares
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    // Mints to `recipient` when given, so a contract can wrap shares on someone else's behalf
+    Wrap{
+        recipient: Option<String>
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    TokenInfo{},
+    Balance{
+        address: String
+    },
+    Allowance{
+        owner: String,
+        spender: String
+    },
+    Config{},
+    WrappedToStaked{
+        amount: Uint128
+    },
+    StakedToWrapped{
+        amount: Uint128
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub staking: String
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WrappedToStakedResponse {
+    pub staked: Uint128
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakedToWrappedResponse {
+    pub wrapped: Uint128
+}